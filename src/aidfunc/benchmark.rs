@@ -12,7 +12,8 @@ use crate::{LocalSearch, Problem, TerminationFunction};
 /// * `runs`: how many runs, is used when seeds is None
 /// * `seeds`: seeds to be used, also dictates amount of runs
 ///
-/// returns: Vec<Vec<Vec<Vec<(u128, f64, f64, u64)>>>>
+/// returns: Vec<Vec<Vec<Vec<(u128, f64, f64, u64)>>>>, or the error of the first run that
+/// failed.
 ///
 
 pub fn benchmark(
@@ -21,7 +22,7 @@ pub fn benchmark(
     termination_function: Option<TerminationFunction>,
     runs: Option<u64>,
     seeds: Option<Vec<u64>>,
-) -> Vec<Vec<Vec<Vec<(u128, f64, f64, u64)>>>> {
+) -> Result<Vec<Vec<Vec<Vec<(u128, f64, f64, u64)>>>>, String> {
     let seed_list: Vec<u64> = seeds.unwrap_or((0..runs.unwrap_or(10)).collect());
 
     let mut res: Vec<Vec<Vec<Vec<(u128, f64, f64, u64)>>>> = Vec::new();
@@ -37,7 +38,7 @@ pub fn benchmark(
             for i in &seed_list {
                 problem.lock().unwrap().set_seed(*i);
                 problem.lock().unwrap().reset();
-                let res = algorithm.lock().unwrap().run(true);
+                let res = algorithm.lock().unwrap().run(true, None)?;
 
                 problem_res.push(res);
             }
@@ -45,5 +46,49 @@ pub fn benchmark(
         }
         res.push(algo_res)
     }
-    res
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::local_search::{FirstImprovement, LocalSearch, SteepestDescent};
+    use crate::problem::{ArrayProblem, ElementProblem, Evaluation, MoveType, Problem};
+    use crate::termination::TerminationFunction;
+
+    use super::benchmark;
+
+    #[test]
+    fn benchmark_error_test() {
+        let eval = Evaluation::tsp(vec![
+            vec![0.0, 1.0, 2.0],
+            vec![1.0, 0.0, 1.0],
+            vec![2.0, 1.0, 0.0],
+        ]);
+        let array_problem: Arc<Mutex<dyn ElementProblem>> = Arc::new(Mutex::new(
+            ArrayProblem::new(&MoveType::three_opt(Some(0)), &eval),
+        ));
+        let problem: Arc<Mutex<dyn Problem>> = array_problem.clone();
+        let termination = TerminationFunction::max_iterations(5);
+        // 3-opt moves can't be restricted to a single element.
+        let first_improvement: Arc<Mutex<dyn LocalSearch>> = Arc::new(Mutex::new(
+            FirstImprovement::new(true, &array_problem, &termination),
+        ));
+        let result = benchmark(
+            vec![first_improvement.clone()],
+            vec![problem.clone()],
+            None,
+            Some(2),
+            None,
+        );
+        assert!(result.is_err());
+        assert!(!first_improvement.is_poisoned());
+
+        let steepest_descent: Arc<Mutex<dyn LocalSearch>> = Arc::new(Mutex::new(
+            SteepestDescent::new(true, &problem, &termination),
+        ));
+        let result = benchmark(vec![steepest_descent], vec![problem], None, Some(2), None);
+        assert_eq!(result.unwrap()[0][0].len(), 2);
+    }
 }
@@ -1,9 +1,10 @@
 #![allow(non_snake_case)]
 use local_search::*;
 use problem::*;
-use pyo3::{exceptions::PyValueError, prelude::*};
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyDict};
 
 use simulated_annealing::{CoolingFunction, IterationsTemperature, SimulatedAnnealing};
+use std::cell::RefCell;
 use std::sync::{Arc, Mutex};
 use steepest_descent::SteepestDescent;
 use tabu_search::TabuSearch;
@@ -30,7 +31,7 @@ fn benchmark(
     termination_function: Option<Py<DynTermination>>,
     runs: Option<u64>,
     seeds: Option<Vec<u64>>,
-) -> Vec<Vec<Vec<Vec<(u128, f64, f64, u64)>>>> {
+) -> PyResult<Vec<Vec<Vec<Vec<(u128, f64, f64, u64)>>>>> {
     println!("hii");
     let r_problems: Vec<Arc<Mutex<dyn Problem>>> =
         problems.iter().map(|f| f.get().problem.clone()).collect();
@@ -44,7 +45,55 @@ fn benchmark(
     } else {
         None
     };
-    aidfunc::benchmark(r_algorithms, r_problems, r_term, runs, seeds)
+    with_callbacks(|| aidfunc::benchmark(r_algorithms, r_problems, r_term, runs, seeds))?
+        .map_err(PyErr::new::<PyValueError, _>)
+}
+
+thread_local! {
+    /// First error raised by a Python callback. The Rust traits can't return it, so it waits
+    /// here until the method called from Python is done.
+    static CALLBACK_ERROR: RefCell<Option<PyErr>> = const { RefCell::new(None) };
+}
+
+/// Value returned by a Python callback.
+fn extract_result<T: for<'py> FromPyObject<'py>>(
+    py: Python<'_>,
+    result: PyResult<PyObject>,
+) -> PyResult<T> {
+    result.and_then(|value| value.extract::<T>(py))
+}
+
+/// Calls Python and extracts the result. A failed call keeps its error and gives `fallback`,
+/// once a call failed the later ones give `fallback` without calling Python.
+fn callback<T: for<'py> FromPyObject<'py>>(
+    fallback: T,
+    call: impl FnOnce(Python<'_>) -> PyResult<PyObject>,
+) -> T {
+    Python::with_gil(|py| {
+        if CALLBACK_ERROR.with(|error| error.borrow().is_some()) {
+            return fallback;
+        }
+        extract_result(py, call(py)).unwrap_or_else(|err| {
+            keep_callback_error(err);
+            fallback
+        })
+    })
+}
+
+fn keep_callback_error(err: PyErr) {
+    CALLBACK_ERROR.with(|error| {
+        error.borrow_mut().get_or_insert(err);
+    });
+}
+
+/// Runs `f`, failing with the first error a Python callback raised during it.
+fn with_callbacks<T>(f: impl FnOnce() -> T) -> PyResult<T> {
+    CALLBACK_ERROR.with(|error| error.borrow_mut().take());
+    let value = f();
+    match CALLBACK_ERROR.with(|error| error.borrow_mut().take()) {
+        Some(err) => Err(err),
+        None => Ok(value),
+    }
 }
 
 // ====================================================================================================================================================================
@@ -52,11 +101,55 @@ fn benchmark(
 // ====================================================================================================================================================================
 #[pyclass(frozen, name = "MoveType")]
 struct DynMoveType {
-    mov: MoveType,
+    mov: AnyMoveType,
+}
+/// Move type of any kind of problem, Python has one MoveType class for all of them.
+#[derive(Clone)]
+enum AnyMoveType {
+    Discrete(MoveType),
+    Real(RealMoveType),
+}
+impl From<MoveType> for AnyMoveType {
+    fn from(move_type: MoveType) -> Self {
+        AnyMoveType::Discrete(move_type)
+    }
+}
+impl From<RealMoveType> for AnyMoveType {
+    fn from(move_type: RealMoveType) -> Self {
+        AnyMoveType::Real(move_type)
+    }
 }
 #[pyclass(frozen, name = "Evaluation")]
 struct DynEvaluation {
-    eva: Evaluation,
+    eva: AnyEvaluation,
+}
+/// Evaluation of any kind of problem, Python has one Evaluation class for all of them.
+#[derive(Clone)]
+enum AnyEvaluation {
+    Permutation(Evaluation),
+    Binary(BinaryEvaluation),
+    Assignment(AssignmentEvaluation),
+    Real(RealEvaluation),
+}
+impl From<Evaluation> for AnyEvaluation {
+    fn from(evaluation: Evaluation) -> Self {
+        AnyEvaluation::Permutation(evaluation)
+    }
+}
+impl From<AssignmentEvaluation> for AnyEvaluation {
+    fn from(evaluation: AssignmentEvaluation) -> Self {
+        AnyEvaluation::Assignment(evaluation)
+    }
+}
+impl From<RealEvaluation> for AnyEvaluation {
+    fn from(evaluation: RealEvaluation) -> Self {
+        AnyEvaluation::Real(evaluation)
+    }
+}
+impl From<BinaryEvaluation> for AnyEvaluation {
+    fn from(evaluation: BinaryEvaluation) -> Self {
+        AnyEvaluation::Binary(evaluation)
+    }
 }
 #[pyclass(frozen, name = "Termination")]
 struct DynTermination {
@@ -65,6 +158,16 @@ struct DynTermination {
 #[pyclass(frozen, name = "Problem")]
 struct DynProblem {
     problem: Arc<Mutex<dyn Problem>>,
+    /// The same problem with its concrete type.
+    handle: ProblemHandle,
+}
+/// Concrete type of a [DynProblem], for what only one kind of problem has.
+enum ProblemHandle {
+    Array(Arc<Mutex<ArrayProblem>>),
+    Binary(Arc<Mutex<BinaryProblem>>),
+    Assignment(Arc<Mutex<AssignmentProblem>>),
+    Real(Arc<Mutex<RealVectorProblem>>),
+    Custom(Arc<Mutex<PyProblem>>),
 }
 
 #[pyclass(frozen, name = "LocalSearch")]
@@ -82,6 +185,214 @@ struct DynIterTemp {
     iter_temp: IterationsTemperature,
 }
 
+#[pyclass(frozen, name = "Construction")]
+struct DynConstruction {
+    construction: Construction,
+}
+
+/// Neighborhood delegating to a Python object with `get_mov`, `get_all_mov` and `do_move`
+/// methods and an optional `set_seed` method.
+struct PyNeighborhood {
+    object: PyObject,
+}
+impl Neighborhood for PyNeighborhood {
+    fn get_mov(&self) -> Vec<usize> {
+        callback(vec![], |py| self.object.call_method0(py, "get_mov"))
+    }
+
+    fn get_all_mov(&self) -> Vec<Vec<usize>> {
+        callback(vec![], |py| self.object.call_method0(py, "get_all_mov"))
+    }
+
+    fn do_move(&self, state: &mut [usize], mov: &[usize]) {
+        let next: Vec<usize> = callback(state.to_vec(), |py| {
+            self.object
+                .call_method1(py, "do_move", (state.to_vec(), mov.to_vec()))
+        });
+        if next.len() != state.len() {
+            keep_callback_error(PyErr::new::<PyValueError, _>(
+                "do_move changed the state length",
+            ));
+            return;
+        }
+        state.copy_from_slice(&next);
+    }
+
+    fn set_seed(&self, seed: u64) {
+        let has_set_seed =
+            Python::with_gil(|py| self.object.bind(py).hasattr("set_seed").unwrap_or(false));
+        if has_set_seed {
+            callback::<Option<PyObject>>(None, |py| {
+                self.object.call_method1(py, "set_seed", (seed,))
+            });
+        }
+    }
+}
+
+/// Problem delegating to a Python object implementing the methods of [Problem]. Its moves are
+/// lists of integers, the move type is a custom one using the `get_mov` and `get_all_mov`
+/// methods of the same object. The state methods are optional, a state is a list of bools,
+/// ints or floats.
+struct PyProblem {
+    object: PyObject,
+    move_type: MoveType,
+}
+impl PyProblem {
+    fn new(object: PyObject) -> Self {
+        let move_type = MoveType::custom(Arc::new(PyNeighborhood {
+            object: Python::with_gil(|py| object.clone_ref(py)),
+        }));
+        PyProblem { object, move_type }
+    }
+
+    fn set_move_type(&mut self, move_type: MoveType) {
+        self.move_type = move_type;
+    }
+
+    /// Calls a method of the Python object and extracts its result, `fallback` if it failed.
+    fn call<T: for<'py> FromPyObject<'py>>(
+        &self,
+        method: &str,
+        mov: Option<&Move>,
+        fallback: T,
+    ) -> T {
+        callback(fallback, |py| match mov {
+            Some(mov) => self.object.call_method1(py, method, (mov.clone(),)),
+            None => self.object.call_method0(py, method),
+        })
+    }
+
+    /// Whether the Python object has the optional method.
+    fn has(&self, method: &str) -> bool {
+        Python::with_gil(|py| self.object.bind(py).hasattr(method).unwrap_or(false))
+    }
+
+    /// Calls a method giving a state, None if the object lacks it or gave None.
+    fn call_state(&self, method: &str) -> Option<State> {
+        if !self.has(method) {
+            return None;
+        }
+        let value: Option<PyObject> = self.call(method, None, None);
+        Python::with_gil(|py| {
+            let value = value?.into_bound(py);
+            if let Ok(bits) = value.extract() {
+                Some(State::Binary(bits))
+            } else if let Ok(order) = value.extract() {
+                Some(State::Permutation(order))
+            } else if let Ok(x) = value.extract() {
+                Some(State::Real(x))
+            } else {
+                keep_callback_error(PyErr::new::<PyValueError, _>(format!(
+                    "{} must give a list of bools, ints or floats",
+                    method
+                )));
+                None
+            }
+        })
+    }
+}
+impl Problem for PyProblem {
+    fn get_mov(&mut self) -> Move {
+        self.move_type.get_mov()
+    }
+
+    fn get_all_mov(&mut self) -> Vec<Move> {
+        self.move_type.get_all_mov()
+    }
+
+    fn iter_mov(
+        &mut self,
+        neighborhood: Option<usize>,
+        shuffle: bool,
+    ) -> Box<dyn Iterator<Item = Move>> {
+        Box::new(self.move_type.select(neighborhood).iter_mov(shuffle))
+    }
+
+    fn do_mov(&mut self, mov: &Move) {
+        self.call::<Option<PyObject>>("do_mov", Some(mov), None);
+    }
+
+    fn undo_mov(&mut self, mov: &Move) {
+        self.call::<Option<PyObject>>("undo_mov", Some(mov), None);
+    }
+
+    fn delta_eval(&mut self, mov: &Move) -> f64 {
+        self.call("delta_eval", Some(mov), f64::NAN)
+    }
+
+    fn eval(&self) -> f64 {
+        self.call("eval", None, f64::NAN)
+    }
+
+    fn reset(&mut self) {
+        self.call::<Option<PyObject>>("reset", None, None);
+    }
+
+    fn set_best(&mut self) {
+        self.call::<Option<PyObject>>("set_best", None, None);
+    }
+
+    fn hash(&self) -> u64 {
+        self.call::<i64>("hash", None, 0) as u64
+    }
+
+    fn neighborhood_count(&self) -> usize {
+        self.move_type.neighborhood_count()
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.move_type.set_seed(seed);
+    }
+
+    fn get_state(&self) -> Option<State> {
+        self.call_state("get_state")
+    }
+
+    fn get_best_solution(&self) -> Option<State> {
+        self.call_state("get_best_solution")
+    }
+
+    fn set_state(&mut self, state: &State) -> Result<(), String> {
+        if !self.has("set_state") {
+            return Err("The custom problem has no set_state method".to_string());
+        }
+        callback::<Option<PyObject>>(None, |py| {
+            self.object.call_method1(py, "set_state", (state.clone(),))
+        });
+        Ok(())
+    }
+
+    fn eval_state(&self, state: &State) -> Result<f64, String> {
+        if !self.has("eval_state") {
+            return Err("The custom problem has no eval_state method".to_string());
+        }
+        Ok(callback(f64::NAN, |py| {
+            self.object.call_method1(py, "eval_state", (state.clone(),))
+        }))
+    }
+
+    fn set_warm_start(&mut self, warm_start: Option<State>) -> Result<(), String> {
+        if !self.has("set_warm_start") {
+            return Err("The custom problem has no set_warm_start method".to_string());
+        }
+        callback::<Option<PyObject>>(None, |py| {
+            self.object
+                .call_method1(py, "set_warm_start", (warm_start,))
+        });
+        Ok(())
+    }
+}
+
+/// Converts a Python list to a state of the same kind as `like`.
+fn extract_state(like: &State, state: &Bound<'_, PyAny>) -> PyResult<State> {
+    Ok(match like {
+        State::Permutation(_) => State::Permutation(state.extract()?),
+        State::Assignment(_) => State::Assignment(state.extract()?),
+        State::Binary(_) => State::Binary(state.extract()?),
+        State::Real(_) => State::Real(state.extract()?),
+    })
+}
+
 // ====================================================================================================================================================================
 // Methods
 // ====================================================================================================================================================================
@@ -91,88 +402,547 @@ impl DynEvaluation {
     #[staticmethod]
     fn empty_bins(weights: Vec<f64>, max_fill: f64) -> Self {
         DynEvaluation {
-            eva: Evaluation::bins(weights, max_fill),
+            eva: Evaluation::bins(weights, max_fill).into(),
         }
     }
     #[staticmethod]
     fn empty_space(weights: Vec<f64>, max_fill: f64) -> Self {
         DynEvaluation {
-            eva: Evaluation::empty_space(weights, max_fill),
+            eva: Evaluation::empty_space(weights, max_fill).into(),
         }
     }
     #[staticmethod]
     fn empty_space_exp(weights: Vec<f64>, max_fill: f64) -> Self {
         DynEvaluation {
-            eva: Evaluation::empty_space_exp(weights, max_fill),
+            eva: Evaluation::empty_space_exp(weights, max_fill).into(),
         }
     }
     #[staticmethod]
     fn tsp(distance_matrix: Vec<Vec<f64>>) -> Self {
         DynEvaluation {
-            eva: Evaluation::tsp(distance_matrix),
+            eva: Evaluation::tsp(distance_matrix).into(),
         }
     }
+    /// Open TSP path, optionally from a fixed `start` node and/or to a fixed `end` node.
+    #[staticmethod]
+    #[pyo3(signature = (distance_matrix, start=None, end=None))]
+    fn tsp_path(
+        distance_matrix: Vec<Vec<f64>>,
+        start: Option<usize>,
+        end: Option<usize>,
+    ) -> PyResult<Self> {
+        let nodes = distance_matrix.len();
+        if nodes == 0 || distance_matrix.iter().any(|row| row.len() != nodes) {
+            return Err(PyErr::new::<PyValueError, _>(
+                "The distance matrix must be square and not empty",
+            ));
+        }
+        if start.iter().chain(&end).any(|node| *node >= nodes) {
+            return Err(PyErr::new::<PyValueError, _>(
+                "The fixed ends must be nodes of the distance matrix",
+            ));
+        }
+        if start.is_some() && start == end && nodes > 1 {
+            return Err(PyErr::new::<PyValueError, _>(
+                "The path can't start and end at the same node",
+            ));
+        }
+        Ok(DynEvaluation {
+            eva: Evaluation::tsp_path(distance_matrix, start, end).into(),
+        })
+    }
     #[staticmethod]
     fn qap(distance_matrix: Vec<Vec<f64>>, flow_matrix: Vec<Vec<f64>>) -> Self {
         DynEvaluation {
-            eva: Evaluation::qap(distance_matrix, flow_matrix),
+            eva: Evaluation::qap(distance_matrix, flow_matrix).into(),
+        }
+    }
+    #[staticmethod]
+    #[pyo3(signature = (values, weights, capacity, penalty=None))]
+    fn knapsack(values: Vec<f64>, weights: Vec<f64>, capacity: f64, penalty: Option<f64>) -> Self {
+        DynEvaluation {
+            eva: BinaryEvaluation::knapsack(values, weights, capacity, penalty).into(),
+        }
+    }
+    #[staticmethod]
+    fn max_cut(weight_matrix: Vec<Vec<f64>>) -> Self {
+        DynEvaluation {
+            eva: BinaryEvaluation::max_cut(weight_matrix).into(),
+        }
+    }
+    #[staticmethod]
+    fn qubo(q_matrix: Vec<Vec<f64>>) -> Self {
+        DynEvaluation {
+            eva: BinaryEvaluation::qubo(q_matrix).into(),
+        }
+    }
+    #[staticmethod]
+    fn graph_coloring(weight_matrix: Vec<Vec<f64>>, colors: usize) -> Self {
+        DynEvaluation {
+            eva: AssignmentEvaluation::graph_coloring(weight_matrix, colors).into(),
+        }
+    }
+    #[staticmethod]
+    #[pyo3(signature = (costs, resources, capacities, penalty=None))]
+    fn generalized_assignment(
+        costs: Vec<Vec<f64>>,
+        resources: Vec<Vec<f64>>,
+        capacities: Vec<f64>,
+        penalty: Option<f64>,
+    ) -> Self {
+        DynEvaluation {
+            eva: AssignmentEvaluation::generalized_assignment(
+                costs, resources, capacities, penalty,
+            )
+            .into(),
+        }
+    }
+    #[staticmethod]
+    #[pyo3(signature = (weights, max_fill, bins=None, penalty=None))]
+    fn group_bins(
+        weights: Vec<f64>,
+        max_fill: f64,
+        bins: Option<usize>,
+        penalty: Option<f64>,
+    ) -> Self {
+        DynEvaluation {
+            eva: AssignmentEvaluation::group_bins(weights, max_fill, bins, penalty).into(),
+        }
+    }
+    /// Capacitated vehicle routing on a giant tour, node 0 of the distance matrix is the depot.
+    /// With a number of vehicles the state holds separators between the routes, otherwise the
+    /// giant tour is split optimally.
+    #[staticmethod]
+    #[pyo3(signature = (distance_matrix, demands, capacity, vehicles=None, penalty=None))]
+    fn cvrp(
+        distance_matrix: Vec<Vec<f64>>,
+        demands: Vec<f64>,
+        capacity: f64,
+        vehicles: Option<usize>,
+        penalty: Option<f64>,
+    ) -> PyResult<Self> {
+        let nodes = distance_matrix.len();
+        if nodes == 0 || distance_matrix.iter().any(|row| row.len() != nodes) {
+            return Err(PyErr::new::<PyValueError, _>(
+                "The distance matrix must be square and contain the depot",
+            ));
+        }
+        if demands.len() != nodes {
+            return Err(PyErr::new::<PyValueError, _>("Every node needs a demand"));
+        }
+        if vehicles == Some(0) {
+            return Err(PyErr::new::<PyValueError, _>(
+                "There has to be at least one vehicle",
+            ));
+        }
+        Ok(DynEvaluation {
+            eva: Evaluation::cvrp(distance_matrix, demands, capacity, vehicles, penalty).into(),
+        })
+    }
+    /// TSP with a time window `(earliest, latest)` and service time for every node, node 0
+    /// is the depot. Being early means waiting, lateness costs `penalty` per unit of time.
+    #[staticmethod]
+    #[pyo3(signature = (distance_matrix, time_windows, service_times=None, penalty=None))]
+    fn tsp_tw(
+        distance_matrix: Vec<Vec<f64>>,
+        time_windows: Vec<(f64, f64)>,
+        service_times: Option<Vec<f64>>,
+        penalty: Option<f64>,
+    ) -> PyResult<Self> {
+        let nodes = distance_matrix.len();
+        if nodes == 0 || distance_matrix.iter().any(|row| row.len() != nodes) {
+            return Err(PyErr::new::<PyValueError, _>(
+                "The distance matrix must be square and contain the depot",
+            ));
+        }
+        if time_windows.len() != nodes
+            || service_times
+                .as_ref()
+                .is_some_and(|service_times| service_times.len() != nodes)
+        {
+            return Err(PyErr::new::<PyValueError, _>(
+                "Time windows and service times need a value for every node",
+            ));
+        }
+        if time_windows
+            .iter()
+            .any(|(earliest, latest)| earliest > latest)
+        {
+            return Err(PyErr::new::<PyValueError, _>(
+                "A time window can't close before it opens",
+            ));
+        }
+        Ok(DynEvaluation {
+            eva: Evaluation::tsp_tw(distance_matrix, time_windows, service_times, penalty).into(),
+        })
+    }
+    /// Single machine total weighted tardiness, with `setup_times[i][j]` between job `i` and
+    /// job `j` if given.
+    #[staticmethod]
+    #[pyo3(signature = (processing_times, due_dates, weights=None, setup_times=None))]
+    fn single_machine(
+        processing_times: Vec<f64>,
+        due_dates: Vec<f64>,
+        weights: Option<Vec<f64>>,
+        setup_times: Option<Vec<Vec<f64>>>,
+    ) -> PyResult<Self> {
+        let jobs = processing_times.len();
+        if due_dates.len() != jobs
+            || weights
+                .as_ref()
+                .is_some_and(|weights| weights.len() != jobs)
+        {
+            return Err(PyErr::new::<PyValueError, _>(
+                "Due dates and weights need a value for every job",
+            ));
+        }
+        if let Some(setup_times) = &setup_times {
+            if setup_times.len() != jobs || setup_times.iter().any(|row| row.len() != jobs) {
+                return Err(PyErr::new::<PyValueError, _>(
+                    "Setup times need a value for every pair of jobs",
+                ));
+            }
+        }
+        Ok(DynEvaluation {
+            eva: Evaluation::single_machine(processing_times, due_dates, weights, setup_times)
+                .into(),
+        })
+    }
+    /// Permutation flow shop with `processing_times[job][machine]`, the objective is
+    /// "makespan", "total_flow_time" or "total_tardiness".
+    #[staticmethod]
+    #[pyo3(signature = (processing_times, objective="makespan", due_dates=None, weights=None))]
+    fn flow_shop(
+        processing_times: Vec<Vec<f64>>,
+        objective: &str,
+        due_dates: Option<Vec<f64>>,
+        weights: Option<Vec<f64>>,
+    ) -> PyResult<Self> {
+        let objective = match objective {
+            "makespan" => FlowShopObjective::Makespan,
+            "total_flow_time" => FlowShopObjective::TotalFlowTime,
+            "total_tardiness" => FlowShopObjective::TotalTardiness,
+            _ => {
+                return Err(PyErr::new::<PyValueError, _>(format!(
+                    "Unknown flow shop objective {objective}"
+                )))
+            }
+        };
+        let jobs = processing_times.len();
+        let machines = processing_times.first().map_or(0, Vec::len);
+        if machines == 0 || processing_times.iter().any(|times| times.len() != machines) {
+            return Err(PyErr::new::<PyValueError, _>(
+                "Every job needs a processing time on every machine",
+            ));
+        }
+        if due_dates.is_none() && objective == FlowShopObjective::TotalTardiness {
+            return Err(PyErr::new::<PyValueError, _>(
+                "Total tardiness needs due dates",
+            ));
+        }
+        if due_dates
+            .as_ref()
+            .is_some_and(|due_dates| due_dates.len() != jobs)
+            || weights
+                .as_ref()
+                .is_some_and(|weights| weights.len() != jobs)
+        {
+            return Err(PyErr::new::<PyValueError, _>(
+                "Due dates and weights need a value for every job",
+            ));
+        }
+        Ok(DynEvaluation {
+            eva: Evaluation::flow_shop(processing_times, objective, due_dates, weights).into(),
+        })
+    }
+    /// Linear ordering to be maximized, `weight_matrix[a][b]` counts if `a` comes before `b`.
+    #[staticmethod]
+    fn linear_ordering(weight_matrix: Vec<Vec<f64>>) -> PyResult<Self> {
+        let len = weight_matrix.len();
+        if weight_matrix.iter().any(|row| row.len() != len) {
+            return Err(PyErr::new::<PyValueError, _>(
+                "The weight matrix must be square",
+            ));
+        }
+        Ok(DynEvaluation {
+            eva: Evaluation::linear_ordering(weight_matrix).into(),
+        })
+    }
+    /// Layout of a graph with `nodes` nodes on a line, the objective is "sum" for the minimum
+    /// linear arrangement or "bandwidth".
+    #[staticmethod]
+    #[pyo3(signature = (nodes, edges, objective="sum", weights=None))]
+    fn linear_arrangement(
+        nodes: usize,
+        edges: Vec<(usize, usize)>,
+        objective: &str,
+        weights: Option<Vec<f64>>,
+    ) -> PyResult<Self> {
+        let objective = match objective {
+            "sum" => ArrangementObjective::Sum,
+            "bandwidth" => ArrangementObjective::Bandwidth,
+            _ => {
+                return Err(PyErr::new::<PyValueError, _>(format!(
+                    "Unknown arrangement objective {objective}"
+                )))
+            }
+        };
+        if edges.iter().any(|(u, v)| *u >= nodes || *v >= nodes) {
+            return Err(PyErr::new::<PyValueError, _>(
+                "Edges must connect nodes of the graph",
+            ));
+        }
+        if weights
+            .as_ref()
+            .is_some_and(|weights| weights.len() != edges.len())
+        {
+            return Err(PyErr::new::<PyValueError, _>("Every edge needs a weight"));
+        }
+        Ok(DynEvaluation {
+            eva: Evaluation::linear_arrangement(nodes, edges, objective, weights).into(),
+        })
+    }
+    #[staticmethod]
+    fn sphere(dimension: usize) -> Self {
+        DynEvaluation {
+            eva: RealEvaluation::sphere(dimension).into(),
+        }
+    }
+    #[staticmethod]
+    fn rastrigin(dimension: usize) -> Self {
+        DynEvaluation {
+            eva: RealEvaluation::rastrigin(dimension).into(),
+        }
+    }
+    #[staticmethod]
+    fn rosenbrock(dimension: usize) -> Self {
+        DynEvaluation {
+            eva: RealEvaluation::rosenbrock(dimension).into(),
+        }
+    }
+    #[staticmethod]
+    fn schwefel(dimension: usize) -> Self {
+        DynEvaluation {
+            eva: RealEvaluation::schwefel(dimension).into(),
+        }
+    }
+    #[staticmethod]
+    fn ackley(dimension: usize) -> Self {
+        DynEvaluation {
+            eva: RealEvaluation::ackley(dimension).into(),
+        }
+    }
+    #[staticmethod]
+    #[pyo3(signature = (size, eval, delta=None))]
+    fn custom(size: usize, eval: PyObject, delta: Option<PyObject>) -> Self {
+        let eval: EvalFn = Arc::new(move |order: &[usize]| {
+            callback(f64::NAN, |py| eval.call1(py, (order.to_vec(),)))
+        });
+        let delta = delta.map(|delta| -> DeltaFn {
+            Arc::new(move |order: &[usize], mov: &Move| {
+                callback(f64::NAN, |py| {
+                    delta.call1(py, (order.to_vec(), mov.clone()))
+                })
+            })
+        });
+        DynEvaluation {
+            eva: Evaluation::custom(size, eval, delta).into(),
         }
     }
     #[staticmethod]
     fn tsp_from_dist_matrix(file: &str) -> PyResult<Self> {
         let distance_matrix = aidfunc::io::read_distance_matrix(file)?;
         Ok(DynEvaluation {
-            eva: Evaluation::tsp(distance_matrix),
+            eva: Evaluation::tsp(distance_matrix).into(),
         })
     }
     #[staticmethod]
     fn tsp_from_coord2d(file: &str) -> PyResult<Self> {
         let distance_matrix = aidfunc::io::read_coord2d_to_distance_matrix(file)?;
         Ok(DynEvaluation {
-            eva: Evaluation::tsp(distance_matrix),
+            eva: Evaluation::tsp(distance_matrix).into(),
         })
     }
     #[staticmethod]
     fn tsp_from_dms(file: &str) -> PyResult<Self> {
         let distance_matrix = aidfunc::io::read_dms_to_distance_matrix(file)?;
         Ok(DynEvaluation {
-            eva: Evaluation::tsp(distance_matrix),
+            eva: Evaluation::tsp(distance_matrix).into(),
         })
     }
 }
 
+impl DynMoveType {
+    /// The move type of a permutation, binary state or assignment.
+    fn discrete(&self) -> PyResult<&MoveType> {
+        match &self.mov {
+            AnyMoveType::Discrete(move_type) => Ok(move_type),
+            AnyMoveType::Real(_) => Err(PyErr::new::<PyValueError, _>(
+                "Gaussian and uniform moves only work on a real vector problem",
+            )),
+        }
+    }
+}
+
 #[pymethods]
 impl DynMoveType {
     #[staticmethod]
     #[pyo3(signature = (seed=None))]
     fn swap(seed: Option<u64>) -> Self {
         DynMoveType {
-            mov: MoveType::swap(seed),
+            mov: MoveType::swap(seed).into(),
         }
     }
     #[staticmethod]
     #[pyo3(signature = (seed=None))]
     fn reverse(seed: Option<u64>) -> Self {
         DynMoveType {
-            mov: MoveType::reverse(seed),
+            mov: MoveType::reverse(seed).into(),
         }
     }
     #[staticmethod]
     #[pyo3(signature = ( seed=None))]
     fn swap_tsp(seed: Option<u64>) -> Self {
         DynMoveType {
-            mov: MoveType::tsp(seed),
+            mov: MoveType::tsp(seed).into(),
+        }
+    }
+    #[staticmethod]
+    #[pyo3(signature = (seed=None))]
+    fn relocate(seed: Option<u64>) -> Self {
+        DynMoveType {
+            mov: MoveType::relocate(seed).into(),
+        }
+    }
+    #[staticmethod]
+    #[pyo3(signature = (seed=None))]
+    fn or_opt(seed: Option<u64>) -> Self {
+        DynMoveType {
+            mov: MoveType::or_opt(seed).into(),
+        }
+    }
+    #[staticmethod]
+    #[pyo3(signature = (seed=None))]
+    fn three_opt(seed: Option<u64>) -> Self {
+        DynMoveType {
+            mov: MoveType::three_opt(seed).into(),
+        }
+    }
+    #[staticmethod]
+    #[pyo3(signature = (seed=None))]
+    fn or_3opt(seed: Option<u64>) -> Self {
+        DynMoveType {
+            mov: MoveType::or_3opt(seed).into(),
+        }
+    }
+    #[staticmethod]
+    #[pyo3(signature = (max_len=3, seed=None))]
+    fn block_exchange(max_len: usize, seed: Option<u64>) -> PyResult<Self> {
+        if max_len == 0 {
+            return Err(PyErr::new::<PyValueError, _>(
+                "Blocks need at least one element",
+            ));
+        }
+        Ok(DynMoveType {
+            mov: MoveType::block_exchange(max_len, seed).into(),
+        })
+    }
+    #[staticmethod]
+    #[pyo3(signature = (seed=None))]
+    fn flip(seed: Option<u64>) -> Self {
+        DynMoveType {
+            mov: MoveType::flip(seed).into(),
+        }
+    }
+    #[staticmethod]
+    #[pyo3(signature = (seed=None))]
+    fn flip_pair(seed: Option<u64>) -> Self {
+        DynMoveType {
+            mov: MoveType::flip_pair(seed).into(),
+        }
+    }
+    #[staticmethod]
+    #[pyo3(signature = (seed=None))]
+    fn assign(seed: Option<u64>) -> Self {
+        DynMoveType {
+            mov: MoveType::assign(seed).into(),
+        }
+    }
+    #[staticmethod]
+    #[pyo3(signature = (step, seed=None))]
+    fn gaussian(step: f64, seed: Option<u64>) -> Self {
+        DynMoveType {
+            mov: RealMoveType::gaussian(step, seed).into(),
+        }
+    }
+    #[staticmethod]
+    #[pyo3(signature = (step, seed=None))]
+    fn uniform(step: f64, seed: Option<u64>) -> Self {
+        DynMoveType {
+            mov: RealMoveType::uniform(step, seed).into(),
+        }
+    }
+    /// Keeps the first and/or last position in place, for paths with a fixed start or end.
+    #[staticmethod]
+    #[pyo3(signature = (move_type, start=true, end=true))]
+    fn fixed_ends(move_type: Py<DynMoveType>, start: bool, end: bool) -> PyResult<Self> {
+        let move_type = move_type.get().discrete()?.clone();
+        if !matches!(
+            move_type,
+            MoveType::Reverse { .. }
+                | MoveType::Swap { .. }
+                | MoveType::Tsp { .. }
+                | MoveType::Relocate { .. }
+                | MoveType::ThreeOpt { .. }
+                | MoveType::BlockExchange { .. }
+        ) {
+            return Err(PyErr::new::<PyValueError, _>(
+                "Only permutation neighborhoods can keep their ends in place",
+            ));
+        }
+        Ok(DynMoveType {
+            mov: MoveType::fixed_ends(move_type, start, end).into(),
+        })
+    }
+    #[staticmethod]
+    fn custom(neighborhood: Bound<'_, PyAny>) -> Result<Self, PyErr> {
+        for method in ["get_mov", "get_all_mov", "do_move"] {
+            if !neighborhood.hasattr(method)? {
+                return Err(PyErr::new::<PyValueError, _>(format!(
+                    "Custom move type needs a {} method",
+                    method
+                )));
+            }
         }
+        Ok(DynMoveType {
+            mov: MoveType::custom(Arc::new(PyNeighborhood {
+                object: neighborhood.unbind(),
+            }))
+            .into(),
+        })
     }
     #[staticmethod]
-    #[pyo3(signature = (move_array, weights=None))]
+    #[pyo3(signature = (move_array, weights=None, seed=None))]
     fn multi_neighbor(
         move_array: Vec<Py<DynMoveType>>,
         weights: Option<Vec<f64>>,
+        seed: Option<u64>,
     ) -> Result<Self, PyErr> {
+        if let Some(weights) = &weights {
+            if weights.len() != move_array.len() {
+                return Err(PyErr::new::<PyValueError, _>(
+                    "Every neighborhood needs a weight",
+                ));
+            }
+            if weights.iter().any(|weight| *weight < 0.0) || weights.iter().sum::<f64>() <= 0.0 {
+                return Err(PyErr::new::<PyValueError, _>(
+                    "Weights can't be negative and need a positive sum",
+                ));
+            }
+        }
         let mut move_types: Vec<MoveType> = vec![];
         for mov in move_array {
-            let cloned_mov = mov.get().mov.clone();
+            let cloned_mov = mov.get().discrete()?.clone();
             if let MoveType::MultiNeighbor { .. } = cloned_mov {
                 return Err(PyErr::new::<PyValueError, _>(
                     "Can't have multi neighbor in multiNeighbor",
@@ -181,7 +951,7 @@ impl DynMoveType {
             move_types.push(cloned_mov);
         }
         Ok(DynMoveType {
-            mov: MoveType::multi_neighbor(move_types, weights),
+            mov: MoveType::multi_neighbor(move_types, weights, seed).into(),
         })
     }
 }
@@ -210,63 +980,115 @@ impl DynLocalSearch {
         })
     }
     #[staticmethod]
+    #[pyo3(signature = (minimize, problem, termination_function, shuffle=false))]
     fn steepest_descent(
         minimize: bool,
         problem: Py<DynProblem>,
         termination_function: Py<DynTermination>,
+        shuffle: bool,
     ) -> PyResult<Self> {
-        let sim = SteepestDescent::new(
+        let mut sim = SteepestDescent::new(
             minimize,
             &problem.get().problem,
             &termination_function.get().termination,
         );
+        sim.set_shuffle(shuffle);
         Ok(DynLocalSearch {
             local_search: Arc::new(Mutex::new(sim)),
         })
     }
     #[staticmethod]
-    #[pyo3(signature = (minimize, problem,termination_function,tabu_list_size=None))]
+    fn first_improvement(
+        minimize: bool,
+        problem: Py<DynProblem>,
+        termination_function: Py<DynTermination>,
+    ) -> PyResult<Self> {
+        let ProblemHandle::Array(array_problem) = &problem.get().handle else {
+            return Err(PyErr::new::<PyValueError, _>(
+                "First improvement needs a problem giving the moves around a single element",
+            ));
+        };
+        let element_problem: Arc<Mutex<dyn ElementProblem>> = array_problem.clone();
+        let sim = FirstImprovement::new(
+            minimize,
+            &element_problem,
+            &termination_function.get().termination,
+        );
+        Ok(DynLocalSearch {
+            local_search: Arc::new(Mutex::new(sim)),
+        })
+    }
+    #[staticmethod]
+    #[pyo3(signature = (minimize, problem,termination_function,tabu_list_size=None, shuffle=false))]
     fn tabu_search(
         minimize: bool,
         problem: Py<DynProblem>,
         termination_function: Py<DynTermination>,
         tabu_list_size: Option<usize>,
+        shuffle: bool,
     ) -> PyResult<Self> {
-        let sim = TabuSearch::new(
+        let mut sim = TabuSearch::new(
             &problem.get().problem,
             &termination_function.get().termination,
             minimize,
             tabu_list_size,
         );
+        sim.set_shuffle(shuffle);
         Ok(DynLocalSearch {
             local_search: Arc::new(Mutex::new(sim)),
         })
     }
 
     #[staticmethod]
+    #[pyo3(signature = (minimize, problem, termination_function, shuffle=false))]
     fn vns(
         minimize: bool,
         problem: Py<DynProblem>,
         termination_function: Py<DynTermination>,
+        shuffle: bool,
     ) -> PyResult<Self> {
-        let sim = VariableNeighborhood::new(
+        let mut sim = VariableNeighborhood::new(
             &problem.get().problem,
             &termination_function.get().termination,
             minimize,
         );
+        sim.set_shuffle(shuffle);
         Ok(DynLocalSearch {
             local_search: Arc::new(Mutex::new(sim)),
         })
     }
 
-    fn run(&self) -> Vec<(u128, f64, f64, u64)> {
+    /// Runs the search, from `initial_solution` if given. With `warm_start` the problem also
+    /// returns to the initial solution on every reset.
+    #[pyo3(signature = (initial_solution=None, warm_start=false))]
+    fn run(
+        &self,
+        initial_solution: Option<Bound<'_, PyAny>>,
+        warm_start: bool,
+    ) -> PyResult<Vec<(u128, f64, f64, u64)>> {
         let mut x = self.local_search.lock().unwrap();
-        x.run(true)
+        let initial_solution = match initial_solution {
+            Some(state) => {
+                let mut problem = x.get_problem().lock().unwrap();
+                let like = with_callbacks(|| problem.get_state())?.ok_or_else(|| {
+                    PyErr::new::<PyValueError, _>("Problem doesn't expose its state")
+                })?;
+                let state = extract_state(&like, &state)?;
+                if warm_start {
+                    with_callbacks(|| problem.set_warm_start(Some(state.clone())))?
+                        .map_err(PyErr::new::<PyValueError, _>)?;
+                }
+                Some(state)
+            }
+            None => None,
+        };
+        with_callbacks(|| x.run(true, initial_solution.as_ref()))?
+            .map_err(PyErr::new::<PyValueError, _>)
     }
 
-    fn reset(&self) {
+    fn reset(&self) -> PyResult<()> {
         let mut x = self.local_search.lock().unwrap();
-        x.reset();
+        with_callbacks(|| x.reset())
     }
 
     fn set_problem(&self, problem: Py<DynProblem>) {
@@ -284,41 +1106,383 @@ impl DynLocalSearch {
     }
 }
 
+impl DynProblem {
+    fn new(handle: ProblemHandle) -> Self {
+        let problem: Arc<Mutex<dyn Problem>> = match &handle {
+            ProblemHandle::Array(problem) => problem.clone(),
+            ProblemHandle::Binary(problem) => problem.clone(),
+            ProblemHandle::Assignment(problem) => problem.clone(),
+            ProblemHandle::Real(problem) => problem.clone(),
+            ProblemHandle::Custom(problem) => problem.clone(),
+        };
+        DynProblem { problem, handle }
+    }
+
+    /// The array problem, `what` names what only array problems have.
+    fn array_handle(&self, what: &str) -> PyResult<&Arc<Mutex<ArrayProblem>>> {
+        match &self.handle {
+            ProblemHandle::Array(problem) => Ok(problem),
+            _ => Err(PyErr::new::<PyValueError, _>(format!(
+                "Only array problems have {}",
+                what
+            ))),
+        }
+    }
+}
+
 #[pymethods]
 impl DynProblem {
     #[staticmethod]
-    fn array_problem(move_type: Py<DynMoveType>, evaluation: Py<DynEvaluation>) -> Self {
-        let move_enum = &move_type.get().mov;
-        let eva = &evaluation.get().eva;
-        DynProblem {
-            problem: Arc::new(Mutex::new(ArrayProblem::new(move_enum, eva))),
+    fn custom(problem: Bound<'_, PyAny>) -> Result<Self, PyErr> {
+        for method in [
+            "get_mov",
+            "get_all_mov",
+            "do_mov",
+            "undo_mov",
+            "delta_eval",
+            "eval",
+            "reset",
+            "set_best",
+            "hash",
+        ] {
+            if !problem.hasattr(method)? {
+                return Err(PyErr::new::<PyValueError, _>(format!(
+                    "Custom problem needs a {} method",
+                    method
+                )));
+            }
         }
+        Ok(DynProblem::new(ProblemHandle::Custom(Arc::new(
+            Mutex::new(PyProblem::new(problem.unbind())),
+        ))))
+    }
+    #[staticmethod]
+    #[pyo3(signature = (move_type, evaluation, construction=None, candidates=None))]
+    fn array_problem(
+        move_type: Py<DynMoveType>,
+        evaluation: Py<DynEvaluation>,
+        construction: Option<Py<DynConstruction>>,
+        candidates: Option<usize>,
+    ) -> Result<Self, PyErr> {
+        let move_enum = move_type.get().discrete()?;
+        let AnyEvaluation::Permutation(eva) = &evaluation.get().eva else {
+            return Err(PyErr::new::<PyValueError, _>(
+                "Array problem needs a permutation evaluation",
+            ));
+        };
+        if !eva.fixed_ends_kept_by(move_enum) {
+            return Err(PyErr::new::<PyValueError, _>(
+                "Move type doesn't keep the fixed ends of the path in place",
+            ));
+        }
+        let mut problem = with_callbacks(|| ArrayProblem::new(move_enum, eva))?;
+        if let Some(construction) = construction {
+            let construction = construction.get().construction.clone();
+            if !construction.supports(eva) {
+                return Err(PyErr::new::<PyValueError, _>(
+                    "Construction doesn't support this evaluation",
+                ));
+            }
+            problem.set_construction(construction);
+            with_callbacks(|| problem.reset())?;
+        }
+        if candidates.is_some() {
+            if !matches!(eva, Evaluation::Tsp { .. } | Evaluation::TspPath { .. }) {
+                return Err(PyErr::new::<PyValueError, _>(
+                    "Candidate lists need a TSP evaluation",
+                ));
+            }
+            problem.set_candidates(candidates);
+        }
+        Ok(DynProblem::new(ProblemHandle::Array(Arc::new(Mutex::new(
+            problem,
+        )))))
+    }
+    #[staticmethod]
+    fn binary_problem(
+        move_type: Py<DynMoveType>,
+        evaluation: Py<DynEvaluation>,
+    ) -> Result<Self, PyErr> {
+        let move_enum = move_type.get().discrete()?;
+        let AnyEvaluation::Binary(eva) = &evaluation.get().eva else {
+            return Err(PyErr::new::<PyValueError, _>(
+                "Binary problem needs a knapsack, max_cut or qubo evaluation",
+            ));
+        };
+        Ok(DynProblem::new(ProblemHandle::Binary(Arc::new(
+            Mutex::new(BinaryProblem::new(move_enum, eva)),
+        ))))
+    }
+    #[staticmethod]
+    #[pyo3(signature = (move_type, evaluation, initial_assignment=None))]
+    fn assignment_problem(
+        move_type: Py<DynMoveType>,
+        evaluation: Py<DynEvaluation>,
+        initial_assignment: Option<Vec<usize>>,
+    ) -> Result<Self, PyErr> {
+        let move_enum = move_type.get().discrete()?;
+        let AnyEvaluation::Assignment(eva) = &evaluation.get().eva else {
+            return Err(PyErr::new::<PyValueError, _>(
+                "Assignment problem needs a graph_coloring, generalized_assignment or group_bins evaluation",
+            ));
+        };
+        if let Some(assignment) = &initial_assignment {
+            if assignment.len() != eva.length() {
+                return Err(PyErr::new::<PyValueError, _>(
+                    "Initial assignment has the wrong length",
+                ));
+            }
+            if assignment.iter().any(|x| *x >= eva.domain()) {
+                return Err(PyErr::new::<PyValueError, _>(
+                    "Initial assignment contains a value outside the domain",
+                ));
+            }
+        }
+        Ok(DynProblem::new(ProblemHandle::Assignment(Arc::new(
+            Mutex::new(AssignmentProblem::new(move_enum, eva, initial_assignment)),
+        ))))
+    }
+    #[staticmethod]
+    #[pyo3(signature = (move_type, evaluation, bounds=None, seed=None))]
+    fn real_vector_problem(
+        move_type: Py<DynMoveType>,
+        evaluation: Py<DynEvaluation>,
+        bounds: Option<Vec<(f64, f64)>>,
+        seed: Option<u64>,
+    ) -> Result<Self, PyErr> {
+        let AnyMoveType::Real(move_enum) = &move_type.get().mov else {
+            return Err(PyErr::new::<PyValueError, _>(
+                "Real vector problem needs a gaussian or uniform move type",
+            ));
+        };
+        let AnyEvaluation::Real(eva) = &evaluation.get().eva else {
+            return Err(PyErr::new::<PyValueError, _>(
+                "Real vector problem needs a sphere, rastrigin, rosenbrock, schwefel or ackley evaluation",
+            ));
+        };
+        if let Some(bounds) = &bounds {
+            if bounds.len() != eva.length() {
+                return Err(PyErr::new::<PyValueError, _>(
+                    "Bounds have the wrong length",
+                ));
+            }
+            if bounds.iter().any(|(lower, upper)| lower > upper) {
+                return Err(PyErr::new::<PyValueError, _>(
+                    "Lower bound is larger than upper bound",
+                ));
+            }
+        }
+        Ok(DynProblem::new(ProblemHandle::Real(Arc::new(Mutex::new(
+            RealVectorProblem::new(move_enum, eva, bounds, seed),
+        )))))
     }
 
-    fn set_eval_type(&self, eval_type: Py<DynEvaluation>) {
-        self.problem
-            .lock()
-            .unwrap()
-            .set_eval_type(eval_type.get().eva.clone());
+    fn set_eval_type(&self, eval_type: Py<DynEvaluation>) -> PyResult<()> {
+        match (&self.handle, eval_type.get().eva.clone()) {
+            (ProblemHandle::Array(problem), AnyEvaluation::Permutation(eval_type)) => {
+                let mut problem = problem.lock().unwrap();
+                problem
+                    .check_eval_type(&eval_type)
+                    .map_err(PyErr::new::<PyValueError, _>)?;
+                with_callbacks(|| problem.set_eval_type(eval_type))?;
+            }
+            (ProblemHandle::Binary(problem), AnyEvaluation::Binary(eval_type)) => {
+                problem.lock().unwrap().set_eval_type(eval_type);
+            }
+            (ProblemHandle::Assignment(problem), AnyEvaluation::Assignment(eval_type)) => {
+                problem.lock().unwrap().set_eval_type(eval_type);
+            }
+            (ProblemHandle::Real(problem), AnyEvaluation::Real(eval_type)) => {
+                problem.lock().unwrap().set_eval_type(eval_type);
+            }
+            (ProblemHandle::Custom(_), _) => {
+                return Err(PyErr::new::<PyValueError, _>(
+                    "A custom problem evaluates itself",
+                ))
+            }
+            _ => {
+                return Err(PyErr::new::<PyValueError, _>(
+                    "The evaluation doesn't fit this kind of problem",
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    fn set_move_type(&self, move_type: Py<DynMoveType>) -> PyResult<()> {
+        match (&self.handle, move_type.get().mov.clone()) {
+            (ProblemHandle::Array(problem), AnyMoveType::Discrete(move_type)) => {
+                let mut problem = problem.lock().unwrap();
+                problem
+                    .check_move_type(&move_type)
+                    .map_err(PyErr::new::<PyValueError, _>)?;
+                problem.set_move_type(move_type);
+            }
+            (ProblemHandle::Binary(problem), AnyMoveType::Discrete(move_type)) => {
+                problem.lock().unwrap().set_move_type(move_type);
+            }
+            (ProblemHandle::Assignment(problem), AnyMoveType::Discrete(move_type)) => {
+                problem.lock().unwrap().set_move_type(move_type);
+            }
+            (ProblemHandle::Custom(problem), AnyMoveType::Discrete(move_type)) => {
+                problem.lock().unwrap().set_move_type(move_type);
+            }
+            (ProblemHandle::Real(problem), AnyMoveType::Real(move_type)) => {
+                problem.lock().unwrap().set_move_type(move_type);
+            }
+            _ => {
+                return Err(PyErr::new::<PyValueError, _>(
+                    "The move type doesn't fit this kind of problem",
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    fn reset(&self) -> PyResult<()> {
+        let mut problem = self.problem.lock().unwrap();
+        with_callbacks(|| problem.reset())
     }
 
-    fn set_move_type(&self, move_type: Py<DynMoveType>) {
-        self.problem
+    fn eval(&self) -> PyResult<f64> {
+        let problem = self.problem.lock().unwrap();
+        with_callbacks(|| problem.eval())
+    }
+
+    fn state(&self) -> PyResult<State> {
+        let problem = self.problem.lock().unwrap();
+        with_callbacks(|| problem.get_state())?
+            .ok_or_else(|| PyErr::new::<PyValueError, _>("Problem doesn't expose its state"))
+    }
+
+    fn best_solution(&self) -> PyResult<State> {
+        let problem = self.problem.lock().unwrap();
+        with_callbacks(|| problem.get_best_solution())?
+            .ok_or_else(|| PyErr::new::<PyValueError, _>("Problem doesn't expose its state"))
+    }
+
+    /// Replaces the current state and returns its score.
+    fn set_state(&self, state: Bound<'_, PyAny>) -> PyResult<f64> {
+        let state = extract_state(&self.state()?, &state)?;
+        let mut problem = self.problem.lock().unwrap();
+        with_callbacks(|| problem.set_state(&state))?.map_err(PyErr::new::<PyValueError, _>)?;
+        with_callbacks(|| problem.eval())
+    }
+
+    /// Gives the score of a state without changing the problem.
+    fn eval_state(&self, state: Bound<'_, PyAny>) -> PyResult<f64> {
+        let state = extract_state(&self.state()?, &state)?;
+        let problem = self.problem.lock().unwrap();
+        with_callbacks(|| problem.eval_state(&state))?.map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// Gives the vehicle routes of a state, by default of the best solution, every route as
+    /// the nodes it visits after leaving the depot.
+    #[pyo3(signature = (state=None))]
+    fn routes(&self, state: Option<Bound<'_, PyAny>>) -> PyResult<Vec<Vec<usize>>> {
+        let state = match state {
+            Some(state) => extract_state(&self.state()?, &state)?,
+            None => self.best_solution()?,
+        };
+        let array_problem = self.array_handle("routes")?.lock().unwrap();
+        array_problem
+            .routes(&state)
+            .map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// Gives the schedule of a time window state, by default of the best solution, as a dict
+    /// with `feasible`, `travel`, `lateness` and `visits` holding a
+    /// `(node, arrival, start, lateness)` tuple for every node up to the return to the depot.
+    #[pyo3(signature = (state=None))]
+    fn feasibility_report<'py>(
+        &self,
+        py: Python<'py>,
+        state: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let state = match state {
+            Some(state) => extract_state(&self.state()?, &state)?,
+            None => self.best_solution()?,
+        };
+        let report = self
+            .array_handle("time windows")?
             .lock()
             .unwrap()
-            .set_move_type(move_type.get().mov.clone());
+            .time_window_report(&state)
+            .map_err(PyErr::new::<PyValueError, _>)?;
+        let visits: Vec<(usize, f64, f64, f64)> = report
+            .visits
+            .iter()
+            .map(|visit| (visit.node, visit.arrival, visit.start, visit.lateness))
+            .collect();
+        let dict = PyDict::new_bound(py);
+        dict.set_item("feasible", report.is_feasible())?;
+        dict.set_item("travel", report.travel)?;
+        dict.set_item("lateness", report.lateness)?;
+        dict.set_item("visits", visits)?;
+        Ok(dict)
     }
 
-    fn reset(&self) {
-        self.problem.lock().unwrap().reset();
+    fn set_seed(&self, seed: u64) -> PyResult<()> {
+        let mut problem = self.problem.lock().unwrap();
+        with_callbacks(|| problem.set_seed(seed))
     }
 
-    fn eval(&self) -> f64 {
-        self.problem.lock().unwrap().eval()
+    /// Sets the state reset restores, None restores the usual initial state.
+    #[pyo3(signature = (state=None))]
+    fn set_warm_start(&self, state: Option<Bound<'_, PyAny>>) -> PyResult<()> {
+        let state = match state {
+            Some(state) => Some(extract_state(&self.state()?, &state)?),
+            None => None,
+        };
+        let mut problem = self.problem.lock().unwrap();
+        with_callbacks(|| problem.set_warm_start(state))?.map_err(PyErr::new::<PyValueError, _>)
     }
+}
 
-    fn set_seed(&self, seed: u64) {
-        self.problem.lock().unwrap().set_seed(seed);
+#[pymethods]
+impl DynConstruction {
+    #[staticmethod]
+    #[pyo3(signature = (seed=None))]
+    fn random(seed: Option<u64>) -> Self {
+        DynConstruction {
+            construction: Construction::random(seed),
+        }
+    }
+    #[staticmethod]
+    fn nearest_neighbor() -> Self {
+        DynConstruction {
+            construction: Construction::nearest_neighbor(),
+        }
+    }
+    #[staticmethod]
+    fn greedy_edge() -> Self {
+        DynConstruction {
+            construction: Construction::greedy_edge(),
+        }
+    }
+    #[staticmethod]
+    fn cheapest_insertion() -> Self {
+        DynConstruction {
+            construction: Construction::cheapest_insertion(),
+        }
+    }
+    #[staticmethod]
+    fn farthest_insertion() -> Self {
+        DynConstruction {
+            construction: Construction::farthest_insertion(),
+        }
+    }
+    #[staticmethod]
+    fn first_fit_decreasing() -> Self {
+        DynConstruction {
+            construction: Construction::first_fit_decreasing(),
+        }
+    }
+    #[staticmethod]
+    fn greedy_qap() -> Self {
+        DynConstruction {
+            construction: Construction::greedy_qap(),
+        }
     }
 }
 
@@ -405,6 +1569,166 @@ fn lclPyO3(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<DynCooling>()?;
     m.add_class::<DynEvaluation>()?;
     m.add_class::<DynMoveType>()?;
+    m.add_class::<DynConstruction>()?;
     m.add_function(wrap_pyfunction!(benchmark, m)?)?;
     Ok(())
 }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn callback_error_test() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::from_code_bound(
+                py,
+                "class Flaky:\n    fail = False\n    def __call__(self, order):\n        if self.fail:\n            self.fail = False\n            raise RuntimeError('flaky')\n        return float(sum(i * x for i, x in enumerate(order)))\n",
+                "flaky.py",
+                "flaky",
+            )
+            .unwrap();
+            let flaky = module.getattr("Flaky").unwrap().call0().unwrap();
+            let evaluation =
+                Py::new(py, DynEvaluation::custom(4, flaky.clone().unbind(), None)).unwrap();
+            let move_type = Py::new(py, DynMoveType::swap(Some(0))).unwrap();
+            let problem = Py::new(
+                py,
+                DynProblem::array_problem(move_type, evaluation, None, None).unwrap(),
+            )
+            .unwrap();
+            let termination = Py::new(py, DynTermination::always_true()).unwrap();
+            let search =
+                DynLocalSearch::steepest_descent(true, problem.clone_ref(py), termination, false)
+                    .unwrap();
+
+            flaky.setattr("fail", true).unwrap();
+            let err = search.run(None, false).unwrap_err();
+            assert!(err.is_instance_of::<pyo3::exceptions::PyRuntimeError>(py));
+
+            // The error left the problem usable, the next run sorts the order descending.
+            search.reset().unwrap();
+            let data = search.run(None, false).unwrap();
+            assert_eq!(data.last().unwrap().1, 4.0);
+            assert_eq!(problem.get().eval().unwrap(), 4.0);
+        });
+    }
+
+    #[test]
+    fn custom_problem_search_test() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::from_code_bound(
+                py,
+                "class Order:
+    def __init__(self):
+        self.order = [0, 3, 1, 2]
+        self.best = None
+    def get_mov(self):
+        return [0, 1]
+    def get_all_mov(self):
+        return [[i, j] for i in range(4) for j in range(i + 1, 4)]
+    def do_mov(self, mov):
+        i, j = mov
+        self.order[i], self.order[j] = self.order[j], self.order[i]
+    undo_mov = do_mov
+    def delta_eval(self, mov):
+        i, j = mov
+        return float((j - i) * (self.order[i] - self.order[j]))
+    def eval(self):
+        return float(sum(i * x for i, x in enumerate(self.order)))
+    def reset(self):
+        self.order = [0, 3, 1, 2]
+    def set_best(self):
+        self.best = list(self.order)
+    def hash(self):
+        return sum(x * 4 ** i for i, x in enumerate(self.order))
+",
+                "order.py",
+                "order",
+            )
+            .unwrap();
+            let object = module.getattr("Order").unwrap().call0().unwrap();
+            let problem = Py::new(py, DynProblem::custom(object.clone()).unwrap()).unwrap();
+            let termination = Py::new(py, DynTermination::always_true()).unwrap();
+            let search =
+                DynLocalSearch::steepest_descent(true, problem.clone_ref(py), termination, false)
+                    .unwrap();
+
+            // The moves, their deltas and the best solution all go through the Python object.
+            let data = search.run(None, false).unwrap();
+            assert_eq!(data.first().unwrap().1, 11.0);
+            assert_eq!(data.last().unwrap().1, 4.0);
+            let best: Vec<usize> = object.getattr("best").unwrap().extract().unwrap();
+            assert_eq!(best, [3, 2, 1, 0]);
+
+            let termination = Py::new(py, DynTermination::max_iterations(10)).unwrap();
+            let search =
+                DynLocalSearch::tabu_search(true, problem.clone_ref(py), termination, None, false)
+                    .unwrap();
+            search.reset().unwrap();
+            assert_eq!(problem.get().eval().unwrap(), 11.0);
+            let data = search.run(None, false).unwrap();
+            assert_eq!(data.last().unwrap().1, 4.0);
+        });
+    }
+
+    #[test]
+    fn custom_problem_state_test() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::from_code_bound(
+                py,
+                "class Bits:
+    def __init__(self):
+        self.bits = [False, True, False]
+    def get_mov(self):
+        return [0]
+    def get_all_mov(self):
+        return [[0], [1], [2]]
+    def do_mov(self, mov):
+        self.bits[mov[0]] = not self.bits[mov[0]]
+    undo_mov = do_mov
+    def delta_eval(self, mov):
+        return -1.0 if self.bits[mov[0]] else 1.0
+    def eval(self):
+        return float(sum(self.bits))
+    def reset(self):
+        pass
+    def set_best(self):
+        pass
+    def hash(self):
+        return sum(1 << i for i, bit in enumerate(self.bits) if bit)
+    def get_state(self):
+        return list(self.bits)
+    def set_state(self, bits):
+        self.bits = list(bits)
+",
+                "bits.py",
+                "bits",
+            )
+            .unwrap();
+            let object = module.getattr("Bits").unwrap().call0().unwrap();
+            let problem = DynProblem::custom(object).unwrap();
+
+            assert_eq!(
+                problem.state().unwrap(),
+                State::Binary(vec![false, true, false])
+            );
+            let bits = vec![true, true, false].into_py(py).into_bound(py);
+            assert_eq!(problem.set_state(bits).unwrap(), 2.0);
+            assert_eq!(
+                problem.state().unwrap(),
+                State::Binary(vec![true, true, false])
+            );
+            // Without a get_best_solution method the best solution stays hidden.
+            assert!(problem.best_solution().is_err());
+            assert!(problem.set_warm_start(None).is_err());
+
+            let evaluation = Py::new(py, DynEvaluation::sphere(3)).unwrap();
+            let err = problem.set_eval_type(evaluation).unwrap_err();
+            assert!(err.is_instance_of::<PyValueError>(py));
+            assert_eq!(problem.eval().unwrap(), 2.0);
+        });
+    }
+}
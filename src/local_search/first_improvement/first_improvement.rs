@@ -0,0 +1,239 @@
+use super::LocalSearch;
+use crate::problem::{ElementProblem, Problem, State};
+use crate::termination::TerminationFunction;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Smallest change counted as an improvement. Smaller changes are rounding noise of the delta
+/// evaluation, a move and its inverse could otherwise both look improving.
+const EPSILON: f64 = 1e-9;
+
+/// Descent doing the first improving move around an element, with a don't-look bit per
+/// element. Only elements in the queue are looked at, an element gets queued again when a
+/// move changes its neighbors.
+pub struct FirstImprovement {
+    /// The problem searched.
+    pub(crate) element_problem: Arc<Mutex<dyn ElementProblem>>,
+    /// The same problem, or the one set since, which can't be searched.
+    pub(crate) problem: Arc<Mutex<dyn Problem>>,
+    pub(crate) termination: TerminationFunction,
+    minimize: bool,
+}
+impl FirstImprovement {
+    pub fn new(
+        minimize: bool,
+        problem: &Arc<Mutex<dyn ElementProblem>>,
+        termination: &TerminationFunction,
+    ) -> Self {
+        let mut term = termination.clone();
+        term.set_goal(minimize);
+        FirstImprovement {
+            element_problem: problem.clone(),
+            problem: problem.clone(),
+            termination: term,
+            minimize,
+        }
+    }
+}
+impl LocalSearch for FirstImprovement {
+    fn reset(&mut self) {
+        self.problem.lock().unwrap().reset();
+    }
+    /// Runs first improvement descent with don't-look bits, fails if the move type can't give
+    /// the moves around a single element or another problem was [set](LocalSearch::set_problem).
+    ///
+    /// # Arguments
+    ///
+    /// * `log`: Whether intermediate results are tracked or not.
+    ///
+    /// returns: a vector of tuples.
+    /// tuple.0 = a timestamp
+    /// tuple.1 = best score found
+    /// tuple.2 = current score
+    /// tuple.3 = #iterations
+    ///
+    /// # Examples
+    ///
+    /// ```
+    ///# use std::sync::{Arc, Mutex};
+    ///# use lclPyO3::local_search::{FirstImprovement, LocalSearch};
+    ///# use lclPyO3::problem::{ArrayProblem, ElementProblem, Evaluation, MoveType};
+    ///# use lclPyO3::termination::TerminationFunction;
+    ///    let distance_matrix: Vec<Vec<f64>> = vec![
+    ///        vec![0.0, 2.0, 5.0, 8.0],
+    ///        vec![2.0, 0.0, 4.0, 1.0],
+    ///        vec![5.0, 4.0, 0.0, 7.0],
+    ///        vec![8.0, 1.0, 7.0, 0.0],
+    ///    ];
+    ///# let move_type=MoveType::reverse(Some(0)) ;
+    ///# let eval=Evaluation::tsp(distance_matrix);
+    ///# let problem:Arc<Mutex<dyn ElementProblem>>=Arc::new(Mutex::new(ArrayProblem::new(&move_type,&eval)));
+    ///# let termination=TerminationFunction::always_true();
+    ///
+    /// let mut sim=FirstImprovement::new(true,&problem,&termination);
+    /// let data=sim.run(false, None).unwrap().last().unwrap().1;
+    /// assert_eq!(data,15.0);
+    /// ```
+    fn run(
+        &mut self,
+        log: bool,
+        initial_solution: Option<&State>,
+    ) -> Result<Vec<(u128, f64, f64, u64)>, String> {
+        if !std::ptr::addr_eq(
+            Arc::as_ptr(&self.problem),
+            Arc::as_ptr(&self.element_problem),
+        ) {
+            return Err(
+                "First improvement can only search the problem it was made with".to_string(),
+            );
+        }
+        let mut problem = self.element_problem.lock().unwrap();
+        if let Some(state) = initial_solution {
+            problem.set_state(state)?;
+        }
+        let elements = problem.element_count();
+        if elements == 0 {
+            return Err("The problem can't give the moves around a single element".to_string());
+        }
+        let mut current = problem.eval();
+        let mut best = current;
+        let now = Instant::now();
+        let mut iterations = 0;
+        let mut data: Vec<(u128, f64, f64, u64)> = vec![];
+        let mut queue: VecDeque<usize> = (0..elements).collect();
+        let mut queued = vec![true; elements];
+
+        problem.set_best();
+        self.termination.init();
+        if log {
+            data.push((now.elapsed().as_nanos(), best, current, iterations));
+        }
+        while self.termination.keep_running() {
+            let Some(element) = queue.pop_front() else {
+                break;
+            };
+            queued[element] = false;
+            let improvement = problem.get_mov_around(element).into_iter().find_map(|mov| {
+                let delta = problem.delta_eval(&mov);
+                let improves = if self.minimize {
+                    delta < -EPSILON
+                } else {
+                    delta > EPSILON
+                };
+                improves.then_some((mov, delta))
+            });
+            let Some((mov, delta)) = improvement else {
+                continue;
+            };
+
+            for touched in problem.touched_elements(&mov).into_iter().chain([element]) {
+                if !queued[touched] {
+                    queued[touched] = true;
+                    queue.push_back(touched);
+                }
+            }
+            problem.do_mov(&mov);
+            current += delta;
+            self.termination.check_new_variable(current);
+            problem.set_best();
+            best = current;
+            if log {
+                data.push((now.elapsed().as_nanos(), best, current, iterations));
+            }
+            iterations += 1;
+            self.termination.iteration_done();
+        }
+        data.push((now.elapsed().as_nanos(), best, current, iterations));
+
+        Ok(data)
+    }
+
+    fn get_problem(&self) -> &Arc<Mutex<dyn Problem>> {
+        &self.problem
+    }
+
+    fn set_problem(&mut self, problem: &Arc<Mutex<dyn Problem>>) {
+        self.problem = problem.clone();
+    }
+
+    fn set_termination(&mut self, termination: &TerminationFunction) {
+        self.termination = termination.clone();
+    }
+}
+#[cfg(test)]
+mod tests {
+    use crate::local_search::{FirstImprovement, LocalSearch};
+    use crate::problem::{ArrayProblem, ElementProblem, Evaluation, MoveType, Problem};
+    use crate::termination::TerminationFunction;
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+    use std::sync::{Arc, Mutex};
+
+    fn random_tsp(len: usize) -> Evaluation {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let points: Vec<(f64, f64)> = (0..len).map(|_| (rng.gen(), rng.gen())).collect();
+        let distance_matrix = points
+            .iter()
+            .map(|a| {
+                points
+                    .iter()
+                    .map(|b| ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt())
+                    .collect()
+            })
+            .collect();
+        Evaluation::tsp(distance_matrix)
+    }
+
+    #[test]
+    fn first_improvement_test() {
+        let eval = random_tsp(60);
+        let termination = TerminationFunction::always_true();
+        for move_type in [MoveType::reverse(Some(0)), MoveType::or_opt(Some(0))] {
+            let mut array_problem = ArrayProblem::new(&move_type, &eval);
+            array_problem.set_candidates(Some(8));
+            let problem: Arc<Mutex<dyn ElementProblem>> = Arc::new(Mutex::new(array_problem));
+            let start = problem.lock().unwrap().eval();
+
+            let mut sim = FirstImprovement::new(true, &problem, &termination);
+            let score = sim.run(false, None).unwrap().last().unwrap().1;
+            assert!(score < start / 2.0);
+
+            // Every don't-look bit is set, so no element has an improving move left.
+            let mut problem = problem.lock().unwrap();
+            assert!((problem.eval() - score).abs() < 1e-9);
+            for element in 0..60 {
+                for mov in problem.get_mov_around(element) {
+                    assert!(problem.delta_eval(&mov) > -1e-9);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unsupported_test() {
+        let problem: Arc<Mutex<dyn ElementProblem>> = Arc::new(Mutex::new(ArrayProblem::new(
+            &MoveType::three_opt(Some(0)),
+            &random_tsp(5),
+        )));
+        let termination = TerminationFunction::always_true();
+        let mut sim = FirstImprovement::new(true, &problem, &termination);
+        assert!(sim.run(false, None).is_err());
+
+        // Only the problem it was made with gives the moves around an element.
+        let reverse: Arc<Mutex<dyn ElementProblem>> = Arc::new(Mutex::new(ArrayProblem::new(
+            &MoveType::reverse(Some(0)),
+            &random_tsp(5),
+        )));
+        let mut sim = FirstImprovement::new(true, &reverse, &termination);
+        assert!(sim.run(false, None).is_ok());
+        let other: Arc<Mutex<dyn Problem>> = Arc::new(Mutex::new(ArrayProblem::new(
+            &MoveType::reverse(Some(0)),
+            &random_tsp(5),
+        )));
+        sim.set_problem(&other);
+        assert!(sim.run(false, None).is_err());
+        let same: Arc<Mutex<dyn Problem>> = reverse.clone();
+        sim.set_problem(&same);
+        assert!(sim.run(false, None).is_ok());
+    }
+}
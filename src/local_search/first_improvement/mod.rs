@@ -0,0 +1,3 @@
+pub mod first_improvement;
+pub use self::first_improvement::FirstImprovement;
+pub use super::LocalSearch;
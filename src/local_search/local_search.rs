@@ -1,9 +1,9 @@
 use std::sync::{Arc, Mutex};
 
-use crate::{Problem, TerminationFunction};
+use crate::{problem::State, Problem, TerminationFunction};
 
 pub trait LocalSearch: Send {
-    /// Resets the state of the problem to ascending indices.
+    /// Resets the state of the problem to its initial state.
     fn reset(&mut self);
 
     /// Runs the given meta heuristic.
@@ -11,13 +11,22 @@ pub trait LocalSearch: Send {
     /// # Arguments
     ///
     /// * `log`: Whether intermediate results are tracked or not.
+    /// * `initial_solution`: state the search starts from instead of the current state of the
+    ///   problem, fails if it isn't a valid state of the problem.
     ///
     /// returns: a vector of tuples.
     /// tuple.0 = a timestamp
     /// tuple.1 = best score found
     /// tuple.2 = current score
     /// tuple.3 = #iterations
-    fn run(&mut self, log: bool) -> Vec<(u128, f64, f64, u64)>;
+    fn run(
+        &mut self,
+        log: bool,
+        initial_solution: Option<&State>,
+    ) -> Result<Vec<(u128, f64, f64, u64)>, String>;
+
+    ///Getter for internal problem
+    fn get_problem(&self) -> &Arc<Mutex<dyn Problem>>;
 
     ///Setter for internal problem
     fn set_problem(&mut self, problem: &Arc<Mutex<dyn Problem>>);
@@ -1,8 +1,10 @@
+pub mod first_improvement;
 pub mod local_search;
 pub mod simulated_annealing;
 pub mod steepest_descent;
 pub mod tabu_search;
 pub mod vns;
+pub use self::first_improvement::FirstImprovement;
 pub use self::local_search::LocalSearch;
 pub use self::simulated_annealing::SimulatedAnnealing;
 pub use self::steepest_descent::SteepestDescent;
@@ -1,6 +1,6 @@
 use super::*;
 use crate::termination::TerminationFunction;
-use crate::{problem::Problem, MoveType};
+use crate::{problem::Problem, problem::State};
 use rand::Rng;
 use std::{
     sync::{Arc, Mutex},
@@ -81,11 +81,18 @@ impl LocalSearch for SimulatedAnnealing {
     ///# let iter=ConstIterTemp {iterations:1000};
     ///
     /// let mut sim=SimulatedAnnealing::new(2000,true,&problem,&termination,&cooling,&iter);
-    /// let data=sim.run(false).last().unwrap().1;
+    /// let data=sim.run(false, None).unwrap().last().unwrap().1;
     /// assert_eq!(data,15.0);
     /// ```
-    fn run(&mut self, log: bool) -> Vec<(u128, f64, f64, u64)> {
+    fn run(
+        &mut self,
+        log: bool,
+        initial_solution: Option<&State>,
+    ) -> Result<Vec<(u128, f64, f64, u64)>, String> {
         let mut problem = self.problem.lock().unwrap();
+        if let Some(state) = initial_solution {
+            problem.set_state(state)?;
+        }
         self.temp = self.start_temp;
         let e = std::f64::consts::E;
         let mut iterations = 0;
@@ -109,10 +116,10 @@ impl LocalSearch for SimulatedAnnealing {
                 }
 
                 let mov = problem.get_mov();
-                let delta = problem.delta_eval(mov, None);
+                let delta = problem.delta_eval(&mov);
 
                 if (delta <= 0.0) == self.minimize || (delta >= 0.0) != self.minimize {
-                    problem.do_mov(mov, None);
+                    problem.do_mov(&mov);
                     current += delta;
                     if (current < best) == self.minimize {
                         problem.set_best();
@@ -127,7 +134,7 @@ impl LocalSearch for SimulatedAnnealing {
                     let probability: f64 = e.powf(exp);
                     let random: f64 = rng.gen();
                     if probability > random {
-                        problem.do_mov(mov, None);
+                        problem.do_mov(&mov);
                         current += delta;
                         if log {
                             data.push((now.elapsed().as_nanos(), best, current, iterations));
@@ -144,15 +151,15 @@ impl LocalSearch for SimulatedAnnealing {
         }
 
         data.push((now.elapsed().as_nanos(), best, current, iterations));
-        data
+        Ok(data)
+    }
+
+    fn get_problem(&self) -> &Arc<Mutex<dyn Problem>> {
+        &self.problem
     }
 
     fn set_problem(&mut self, problem: &Arc<Mutex<dyn Problem>>) {
-        if let MoveType::MultiNeighbor { .. } = problem.lock().unwrap().get_move_type() {
-            panic!("Can't use multiNeighbor in Simulated Annealing")
-        } else {
-            self.problem = problem.clone();
-        }
+        self.problem = problem.clone();
     }
 
     fn set_termination(&mut self, termination: &TerminationFunction) {
@@ -185,7 +192,32 @@ mod tests {
         let iter = IterationsTemperature::const_iter_temp(1000);
 
         let mut sim = SimulatedAnnealing::new(2000, true, &problem, &termination, &cooling, &iter);
-        let data = sim.run(false).last().unwrap().1;
+        let data = sim.run(false, None).unwrap().last().unwrap().1;
+        assert_eq!(data, 15.0);
+    }
+    #[test]
+    fn multi_neighbor_test() {
+        let distance_matrix: Vec<Vec<f64>> = vec![
+            vec![0.0, 2.0, 5.0, 8.0],
+            vec![2.0, 0.0, 4.0, 1.0],
+            vec![5.0, 4.0, 0.0, 7.0],
+            vec![8.0, 1.0, 7.0, 0.0],
+        ];
+        let move_type = MoveType::multi_neighbor(
+            vec![MoveType::tsp(Some(0)), MoveType::reverse(Some(0))],
+            Some(vec![1.0, 3.0]),
+            Some(0),
+        );
+        let eval = Evaluation::tsp(distance_matrix);
+        let problem: Arc<Mutex<dyn Problem>> =
+            Arc::new(Mutex::new(ArrayProblem::new(&move_type, &eval)));
+        let cooling = CoolingFunction::geometric_cooling(0.75);
+        let termination = TerminationFunction::min_temp(10);
+        let iter = IterationsTemperature::const_iter_temp(1000);
+
+        let mut sim = SimulatedAnnealing::new(2000, true, &problem, &termination, &cooling, &iter);
+        sim.set_problem(&problem);
+        let data = sim.run(false, None).unwrap().last().unwrap().1;
         assert_eq!(data, 15.0);
     }
 }
@@ -1,7 +1,7 @@
 use super::LocalSearch;
-use crate::problem::Problem;
+use crate::problem::{Problem, State};
 use crate::termination::TerminationFunction;
-use crate::MoveType;
+use crate::Move;
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
@@ -9,6 +9,7 @@ pub struct SteepestDescent {
     pub(crate) problem: Arc<Mutex<dyn Problem>>,
     pub(crate) termination: TerminationFunction,
     minimize: bool,
+    shuffle: bool,
 }
 impl SteepestDescent {
     pub fn new(
@@ -22,8 +23,14 @@ impl SteepestDescent {
             problem: problem.clone(),
             termination: term,
             minimize,
+            shuffle: false,
         }
     }
+
+    /// Visits the moves in a random order every iteration, so ties go to a random best move.
+    pub fn set_shuffle(&mut self, shuffle: bool) {
+        self.shuffle = shuffle;
+    }
 }
 impl LocalSearch for SteepestDescent {
     fn reset(&mut self) {
@@ -62,11 +69,18 @@ impl LocalSearch for SteepestDescent {
     ///# let termination=TerminationFunction::always_true();
     ///
     /// let mut sim=SteepestDescent::new(true,&problem,&termination);
-    /// let data=sim.run(false).last().unwrap().1;
+    /// let data=sim.run(false, None).unwrap().last().unwrap().1;
     /// assert_eq!(data,15.0);
     /// ```
-    fn run(&mut self, log: bool) -> Vec<(u128, f64, f64, u64)> {
+    fn run(
+        &mut self,
+        log: bool,
+        initial_solution: Option<&State>,
+    ) -> Result<Vec<(u128, f64, f64, u64)>, String> {
         let mut problem = self.problem.lock().unwrap();
+        if let Some(state) = initial_solution {
+            problem.set_state(state)?;
+        }
         let mut current = problem.eval();
         let mut best = current;
         let now = Instant::now();
@@ -79,21 +93,24 @@ impl LocalSearch for SteepestDescent {
         }
         while self.termination.keep_running() {
             // while iterations<100{
-            let mut best_mov = (0, 0);
+            let mut best_mov: Option<Move> = None;
             let mut best_delta = if self.minimize { f64::MAX } else { f64::MIN };
-            for mov in problem.get_all_mov() {
-                let delta = problem.delta_eval(mov, None);
+            for mov in problem.iter_mov(None, self.shuffle) {
+                let delta = problem.delta_eval(&mov);
                 if (delta <= best_delta) == self.minimize || (delta >= best_delta) != self.minimize
                 {
                     best_delta = delta;
-                    best_mov = mov;
+                    best_mov = Some(mov);
                 }
             }
+            let Some(mov) = best_mov else {
+                break;
+            };
             current = current + best_delta;
 
             self.termination.check_new_variable(current);
             if (current < best) == self.minimize {
-                problem.do_mov(best_mov, None);
+                problem.do_mov(&mov);
                 problem.set_best();
                 best = current;
                 if log {
@@ -107,15 +124,15 @@ impl LocalSearch for SteepestDescent {
         }
         data.push((now.elapsed().as_nanos(), best, current, iterations));
 
-        data
+        Ok(data)
+    }
+
+    fn get_problem(&self) -> &Arc<Mutex<dyn Problem>> {
+        &self.problem
     }
 
     fn set_problem(&mut self, problem: &Arc<Mutex<dyn Problem>>) {
-        if let MoveType::MultiNeighbor { .. } = problem.lock().unwrap().get_move_type() {
-            panic!("Can't use multiNeighbor in Steepest Descent")
-        } else {
-            self.problem = problem.clone();
-        }
+        self.problem = problem.clone();
     }
 
     fn set_termination(&mut self, termination: &TerminationFunction) {
@@ -125,7 +142,7 @@ impl LocalSearch for SteepestDescent {
 #[cfg(test)]
 mod tests {
     use crate::local_search::{LocalSearch, SteepestDescent};
-    use crate::problem::{ArrayProblem, Evaluation, MoveType, Problem};
+    use crate::problem::{ArrayProblem, Evaluation, MoveType, Problem, State};
     use crate::termination::TerminationFunction;
     use std::sync::{Arc, Mutex};
 
@@ -144,7 +161,71 @@ mod tests {
         let termination = TerminationFunction::always_true();
 
         let mut sim = SteepestDescent::new(true, &problem, &termination);
-        let data = sim.run(false).last().unwrap().1;
+        let data = sim.run(false, None).unwrap().last().unwrap().1;
         assert_eq!(data, 15.0);
     }
+
+    #[test]
+    fn initial_solution_test() {
+        let distance_matrix: Vec<Vec<f64>> = vec![
+            vec![0.0, 2.0, 5.0, 8.0],
+            vec![2.0, 0.0, 4.0, 1.0],
+            vec![5.0, 4.0, 0.0, 7.0],
+            vec![8.0, 1.0, 7.0, 0.0],
+        ];
+        let move_type = MoveType::tsp(Some(0));
+        let eval = Evaluation::tsp(distance_matrix);
+        let problem: Arc<Mutex<dyn Problem>> =
+            Arc::new(Mutex::new(ArrayProblem::new(&move_type, &eval)));
+        let termination = TerminationFunction::always_true();
+
+        let mut sim = SteepestDescent::new(true, &problem, &termination);
+        let invalid = State::Permutation(vec![0, 1, 2]);
+        assert!(sim.run(false, Some(&invalid)).is_err());
+
+        let optimum = State::Permutation(vec![0, 1, 3, 2]);
+        let data = sim.run(true, Some(&optimum)).unwrap();
+        assert_eq!(data[0].1, 15.0);
+        assert_eq!(data.last().unwrap().3, 0);
+    }
+    #[test]
+    fn candidates_test() {
+        // Cities on a line, 2-opt with the two nearest cities untangles the tour.
+        let positions: [f64; 8] = [0.0, 5.0, 2.0, 7.0, 1.0, 6.0, 3.0, 4.0];
+        let distance_matrix: Vec<Vec<f64>> = positions
+            .iter()
+            .map(|a| positions.iter().map(|b| (a - b).abs()).collect())
+            .collect();
+        let move_type = MoveType::reverse(Some(0));
+        let eval = Evaluation::tsp(distance_matrix);
+        let mut array_problem = ArrayProblem::new(&move_type, &eval);
+        array_problem.set_candidates(Some(2));
+        assert!(array_problem.get_all_mov().len() < 8 * 7 / 2);
+        let problem: Arc<Mutex<dyn Problem>> = Arc::new(Mutex::new(array_problem));
+        let termination = TerminationFunction::always_true();
+
+        let mut sim = SteepestDescent::new(true, &problem, &termination);
+        let data = sim.run(false, None).unwrap();
+        assert_eq!(data.last().unwrap().1, 14.0);
+    }
+
+    #[test]
+    fn empty_neighborhood_test() {
+        let distance_matrix: Vec<Vec<f64>> = vec![
+            vec![0.0, 5.0, 1.0],
+            vec![1.0, 0.0, 5.0],
+            vec![5.0, 1.0, 0.0],
+        ];
+        // Keeping both ends of three elements in place leaves no swaps.
+        let move_type = MoveType::fixed_ends(MoveType::swap(Some(0)), true, true);
+        let eval = Evaluation::tsp(distance_matrix);
+        let problem: Arc<Mutex<dyn Problem>> =
+            Arc::new(Mutex::new(ArrayProblem::new(&move_type, &eval)));
+        let termination = TerminationFunction::always_true();
+
+        let mut sim = SteepestDescent::new(true, &problem, &termination);
+        let data = sim.run(false, None).unwrap();
+        let (_, best, current, iterations) = *data.last().unwrap();
+        assert_eq!((best, current, iterations), (15.0, 15.0, 0));
+    }
 }
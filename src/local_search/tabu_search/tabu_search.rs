@@ -1,7 +1,7 @@
 use super::LocalSearch;
-use crate::problem::Problem;
+use crate::problem::{Problem, State};
 use crate::termination::TerminationFunction;
-use crate::MoveType;
+use crate::Move;
 use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
@@ -12,6 +12,7 @@ pub struct TabuSearch {
     pub(crate) termination: TerminationFunction,
     minimize: bool,
     list_size: usize,
+    shuffle: bool,
 }
 impl TabuSearch {
     pub fn new(
@@ -27,8 +28,14 @@ impl TabuSearch {
             termination: term,
             minimize,
             list_size: tabu_list_size.unwrap_or(7),
+            shuffle: false,
         }
     }
+
+    /// Visits the moves in a random order every iteration, so ties go to a random best move.
+    pub fn set_shuffle(&mut self, shuffle: bool) {
+        self.shuffle = shuffle;
+    }
 }
 
 impl LocalSearch for TabuSearch {
@@ -65,12 +72,19 @@ impl LocalSearch for TabuSearch {
     /// let termination=TerminationFunction::max_sec(1);
     ///
     /// let mut sim=TabuSearch::new(&problem,&termination,true,None);
-    /// let data=sim.run(false).last().unwrap().1;
+    /// let data=sim.run(false, None).unwrap().last().unwrap().1;
     ///
     /// assert_eq!(data,15.0);
     /// ```
-    fn run(&mut self, log: bool) -> Vec<(u128, f64, f64, u64)> {
+    fn run(
+        &mut self,
+        log: bool,
+        initial_solution: Option<&State>,
+    ) -> Result<Vec<(u128, f64, f64, u64)>, String> {
         let mut problem = self.problem.lock().unwrap();
+        if let Some(state) = initial_solution {
+            problem.set_state(state)?;
+        }
         let mut current = problem.eval();
         let mut best = current;
         let now = Instant::now();
@@ -83,16 +97,16 @@ impl LocalSearch for TabuSearch {
 
         self.termination.init();
         while self.termination.keep_running() {
-            let mut best_mov: Option<(usize, usize)> = None;
+            let mut best_mov: Option<Move> = None;
             let mut best_delta = if self.minimize { f64::MAX } else { f64::MIN };
             let mut best_hash: u64 = 0;
 
-            for mov in problem.get_all_mov() {
-                let delta = problem.delta_eval(mov, None);
+            for mov in problem.iter_mov(None, self.shuffle) {
+                let delta = problem.delta_eval(&mov);
 
-                problem.do_mov(mov, None);
+                problem.do_mov(&mov);
                 let hash = problem.hash();
-                problem.do_mov(mov, None);
+                problem.undo_mov(&mov);
 
                 if !tabu_list.contains(&hash)
                     && ((delta < best_delta) == self.minimize
@@ -105,7 +119,7 @@ impl LocalSearch for TabuSearch {
             }
             if best_mov.is_some() {
                 current = current + best_delta;
-                problem.do_mov(best_mov.unwrap(), None);
+                problem.do_mov(best_mov.as_ref().unwrap());
                 if (current < best) == self.minimize || (best < current) != self.minimize {
                     best = current;
                     problem.set_best();
@@ -126,15 +140,15 @@ impl LocalSearch for TabuSearch {
             self.termination.iteration_done();
         }
         data.push((now.elapsed().as_nanos(), best, current, iterations));
-        data
+        Ok(data)
+    }
+
+    fn get_problem(&self) -> &Arc<Mutex<dyn Problem>> {
+        &self.problem
     }
 
     fn set_problem(&mut self, problem: &Arc<Mutex<dyn Problem>>) {
-        if let MoveType::MultiNeighbor { .. } = problem.lock().unwrap().get_move_type() {
-            panic!("Can't use multiNeighbor in Tabu Search")
-        } else {
-            self.problem = problem.clone();
-        }
+        self.problem = problem.clone();
     }
 
     fn set_termination(&mut self, termination: &TerminationFunction) {
@@ -163,7 +177,27 @@ mod tests {
         let termination = TerminationFunction::max_iterations(1000);
 
         let mut sim = TabuSearch::new(&problem, &termination, true, None);
-        let data = sim.run(false).last().unwrap().1;
+        let data = sim.run(false, None).unwrap().last().unwrap().1;
+        assert_eq!(data, 15.0);
+    }
+
+    #[test]
+    fn shuffle_test() {
+        let distance_matrix: Vec<Vec<f64>> = vec![
+            vec![0.0, 2.0, 5.0, 8.0],
+            vec![2.0, 0.0, 4.0, 1.0],
+            vec![5.0, 4.0, 0.0, 7.0],
+            vec![8.0, 1.0, 7.0, 0.0],
+        ];
+        let move_type = MoveType::reverse(Some(0));
+        let eval = Evaluation::tsp(distance_matrix);
+        let problem: Arc<Mutex<dyn Problem>> =
+            Arc::new(Mutex::new(ArrayProblem::new(&move_type, &eval)));
+        let termination = TerminationFunction::max_iterations(100);
+
+        let mut sim = TabuSearch::new(&problem, &termination, true, None);
+        sim.set_shuffle(true);
+        let data = sim.run(false, None).unwrap().last().unwrap().1;
         assert_eq!(data, 15.0);
     }
 }
@@ -1,7 +1,7 @@
 use super::LocalSearch;
-use crate::problem::Problem;
+use crate::problem::{Problem, State};
 use crate::termination::TerminationFunction;
-use crate::MoveType;
+use crate::Move;
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use std::vec;
@@ -11,6 +11,7 @@ pub struct VariableNeighborhood {
     termination: TerminationFunction,
     minimize: bool,
     neighborhood: usize,
+    shuffle: bool,
 }
 
 impl VariableNeighborhood {
@@ -26,49 +27,13 @@ impl VariableNeighborhood {
             termination: term,
             minimize,
             neighborhood: 0,
+            shuffle: false,
         }
     }
 
-    fn get_all_mov_select(&self, move_type: &MoveType) -> Vec<(usize, usize)> {
-        match move_type {
-            MoveType::Reverse { rng: _, size: _ } => move_type.get_all_mov(),
-            MoveType::Swap { rng: _, size: _ } => move_type.get_all_mov(),
-            MoveType::Tsp { rng: _, size: _ } => move_type.get_all_mov(),
-            MoveType::MultiNeighbor {
-                move_types,
-                weights: _,
-            } => move_types[self.neighborhood].get_all_mov(),
-        }
-    }
-
-    fn delta_eval(&self, problem: &mut dyn Problem, mov: (usize, usize)) -> f64 {
-        match problem.get_move_type().to_owned() {
-            MoveType::Reverse { .. } | MoveType::Swap { .. } | MoveType::Tsp { .. } => {
-                problem.delta_eval(mov, None)
-            }
-            MoveType::MultiNeighbor {
-                move_types,
-                weights: _,
-            } => {
-                let move_type = Some(&move_types[self.neighborhood]);
-                problem.delta_eval(mov, move_type)
-            }
-        }
-    }
-
-    fn do_move(&self, problem: &mut dyn Problem, best_move: (usize, usize)) {
-        match problem.get_move_type().to_owned() {
-            MoveType::Reverse { .. } | MoveType::Swap { .. } | MoveType::Tsp { .. } => {
-                problem.do_mov(best_move, None)
-            }
-            MoveType::MultiNeighbor {
-                move_types,
-                weights: _,
-            } => {
-                let move_type = Some(&move_types[self.neighborhood]);
-                problem.do_mov(best_move, move_type)
-            }
-        }
+    /// Visits the moves in a random order every iteration, so ties go to a random best move.
+    pub fn set_shuffle(&mut self, shuffle: bool) {
+        self.shuffle = shuffle;
     }
 }
 impl LocalSearch for VariableNeighborhood {
@@ -108,17 +73,24 @@ impl LocalSearch for VariableNeighborhood {
     ///# let move_type_0=MoveType::tsp(Some(0));
     ///# let move_type_1=MoveType::reverse(Some(0));
     ///# let move_type_2=MoveType::swap(Some(0));
-    ///# let move_type=MoveType::multi_neighbor(vec![move_type_0,move_type_1,move_type_2],None);
+    ///# let move_type=MoveType::multi_neighbor(vec![move_type_0,move_type_1,move_type_2],None,None);
     ///# let eval=Evaluation::Tsp {distance_matrix,symmetric:true};
     ///# let problem:Arc<Mutex<dyn Problem>>=Arc::new(Mutex::new(ArrayProblem::new(&move_type,&eval)));
     ///# let termination=TerminationFunction::max_sec(1);
     ///
     /// let mut sim=VariableNeighborhood::new(&problem,&termination,true);
-    /// let data=sim.run(false).last().unwrap().1;
+    /// let data=sim.run(false, None).unwrap().last().unwrap().1;
     /// assert_eq!(data,15.0);
     /// ```
-    fn run(&mut self, log: bool) -> Vec<(u128, f64, f64, u64)> {
+    fn run(
+        &mut self,
+        log: bool,
+        initial_solution: Option<&State>,
+    ) -> Result<Vec<(u128, f64, f64, u64)>, String> {
         let mut problem = self.problem.lock().unwrap();
+        if let Some(state) = initial_solution {
+            problem.set_state(state)?;
+        }
         let mut current = problem.eval();
         let mut best = current;
         let now = Instant::now();
@@ -130,50 +102,46 @@ impl LocalSearch for VariableNeighborhood {
         self.termination.init();
         while self.termination.keep_running() {
             let mut best_delta = if self.minimize { f64::MAX } else { f64::MIN };
-            let mut best_move: Option<(usize, usize)> = None;
+            let mut best_move: Option<Move> = None;
 
-            for mov in self.get_all_mov_select(problem.get_move_type()) {
-                let delta = self.delta_eval(&mut *(problem), mov);
+            for mov in problem.iter_mov(Some(self.neighborhood), self.shuffle) {
+                let delta = problem.delta_eval(&mov);
                 if (delta < best_delta) == self.minimize {
                     best_delta = delta;
                     best_move = Some(mov);
                     continue;
                 }
             }
-            current += best_delta;
-
-            self.termination.check_new_variable(current);
+            // An empty neighborhood can't improve, the search moves on to the next one.
+            if best_move.is_some() {
+                self.termination.check_new_variable(current + best_delta);
+            }
 
-            if (current < best) == self.minimize {
-                self.do_move(&mut *problem, best_move.unwrap());
+            if let Some(mov) = best_move.filter(|_| (current + best_delta < best) == self.minimize)
+            {
+                current += best_delta;
+                problem.do_mov(&mov);
                 problem.set_best();
                 best = current;
                 if log {
                     data.push((now.elapsed().as_nanos(), best, current, iterations))
                 }
             } else {
-                current -= best_delta;
-                match problem.get_move_type() {
-                    MoveType::Reverse { rng: _, size: _ }
-                    | MoveType::Swap { rng: _, size: _ }
-                    | MoveType::Tsp { rng: _, size: _ } => break,
-                    MoveType::MultiNeighbor {
-                        move_types,
-                        weights: _,
-                    } => {
-                        if self.neighborhood + 1 >= move_types.len() {
-                            break;
-                        } else {
-                            self.neighborhood += 1;
-                        }
-                    }
+                if self.neighborhood + 1 >= problem.neighborhood_count() {
+                    break;
+                } else {
+                    self.neighborhood += 1;
                 }
             }
             iterations += 1;
             self.termination.iteration_done();
         }
         data.push((now.elapsed().as_nanos(), best, current, iterations));
-        data
+        Ok(data)
+    }
+
+    fn get_problem(&self) -> &Arc<Mutex<dyn Problem>> {
+        &self.problem
     }
 
     fn set_problem(&mut self, problem: &Arc<Mutex<dyn Problem>>) {
@@ -203,14 +171,42 @@ mod tests {
         let move_type_0 = MoveType::tsp(Some(0));
         let move_type_1 = MoveType::swap(Some(0));
         let move_type_2 = MoveType::reverse(Some(0));
-        let move_type = MoveType::multi_neighbor(vec![move_type_0, move_type_1, move_type_2], None);
+        let move_type =
+            MoveType::multi_neighbor(vec![move_type_0, move_type_1, move_type_2], None, None);
         let eval = Evaluation::tsp(distance_matrix);
         let problem: Arc<Mutex<dyn Problem>> =
             Arc::new(Mutex::new(ArrayProblem::new(&move_type, &eval)));
         let termination = TerminationFunction::max_sec(1);
 
         let mut sim = VariableNeighborhood::new(&problem, &termination, true);
-        let data = sim.run(false).last().unwrap().1;
+        let data = sim.run(false, None).unwrap().last().unwrap().1;
         assert_eq!(data, 15.0);
     }
+
+    #[test]
+    fn empty_neighborhood_test() {
+        let distance_matrix: Vec<Vec<f64>> = vec![
+            vec![0.0, 5.0, 1.0],
+            vec![1.0, 0.0, 5.0],
+            vec![5.0, 1.0, 0.0],
+        ];
+        // Keeping both ends of three elements in place leaves no swaps.
+        let move_type = MoveType::multi_neighbor(
+            vec![
+                MoveType::fixed_ends(MoveType::swap(Some(0)), true, true),
+                MoveType::reverse(Some(0)),
+            ],
+            None,
+            None,
+        );
+        let eval = Evaluation::tsp(distance_matrix);
+        let problem: Arc<Mutex<dyn Problem>> =
+            Arc::new(Mutex::new(ArrayProblem::new(&move_type, &eval)));
+        let termination = TerminationFunction::max_iterations(10);
+
+        let mut sim = VariableNeighborhood::new(&problem, &termination, true);
+        let data = sim.run(false, None).unwrap();
+        let (_, best, current, _) = *data.last().unwrap();
+        assert_eq!((best, current), (3.0, 3.0));
+    }
 }
@@ -3,28 +3,145 @@ use std::{
     hash::{Hash, Hasher},
 };
 
-use super::{Evaluation, MoveType, Problem};
+use super::delta_cache::DeltaCache;
+use super::r#move::positions;
+use super::{
+    Construction, ElementProblem, Evaluation, Move, MoveType, Problem, State, TimeWindowReport,
+};
 
 pub struct ArrayProblem {
     state: Vec<usize>,
     best_solution: Vec<usize>,
     move_type: MoveType,
     evaluation: Evaluation,
+    construction: Construction,
+    warm_start: Option<Vec<usize>>,
+    previous_state: Vec<usize>,
+    candidate_count: Option<usize>,
+    candidates: Vec<Vec<usize>>,
+    /// Index of every element in the state, empty when outdated.
+    positions: Vec<usize>,
+    /// What the evaluation keeps about the state to evaluate moves faster.
+    cache: DeltaCache,
 }
 impl ArrayProblem {
     pub fn new(move_type: &MoveType, evaluation: &Evaluation) -> Self {
+        assert!(
+            evaluation.fixed_ends_kept_by(move_type),
+            "Move type doesn't keep the fixed ends of the path in place"
+        );
         let len = evaluation.length();
         let mut mov = move_type.clone();
         mov.set_size(len);
+        let mut state: Vec<usize> = (0..len).collect();
+        evaluation.place_fixed_ends(&mut state);
         let array_problem = ArrayProblem {
-            state: (0..len).collect(),
-            best_solution: (0..len).collect(),
+            best_solution: state.clone(),
+            state,
             move_type: mov,
             evaluation: evaluation.clone(),
+            construction: Construction::identity(),
+            warm_start: None,
+            previous_state: vec![],
+            candidate_count: None,
+            candidates: vec![],
+            positions: vec![],
+            cache: DeltaCache::new(evaluation),
         };
         array_problem
     }
 
+    /// Sets the heuristic building the order used by [reset](Problem::reset).
+    pub fn set_construction(&mut self, construction: Construction) {
+        assert!(
+            construction.supports(&self.evaluation),
+            "Construction doesn't support this evaluation"
+        );
+        self.construction = construction;
+    }
+
+    /// Restricts [get_all_mov](Problem::get_all_mov) of reverse and swap neighborhoods to
+    /// moves connecting a city with one of its `k` nearest cities, `None` lifts the restriction.
+    pub fn set_candidates(&mut self, k: Option<usize>) {
+        self.candidate_count = k;
+        self.candidates = match k {
+            Some(k) => self
+                .evaluation
+                .nearest_neighbors(k)
+                .expect("Candidate lists need a TSP evaluation"),
+            None => vec![],
+        };
+    }
+
+    /// Sets the evaluation, panics if the current state or the move type don't keep the fixed
+    /// ends of the path.
+    pub fn set_eval_type(&mut self, eval_type: Evaluation) {
+        if let Err(err) = self.check_eval_type(&eval_type) {
+            panic!("{}", err);
+        }
+        self.cache = DeltaCache::new(&eval_type);
+        self.evaluation = eval_type;
+        if let Some(k) = self.candidate_count {
+            match self.evaluation.nearest_neighbors(k) {
+                Some(candidates) => self.candidates = candidates,
+                None => self.set_candidates(None),
+            }
+        }
+    }
+
+    /// Sets the move type, panics if it would move the fixed ends of the path.
+    pub fn set_move_type(&mut self, move_type: MoveType) {
+        if let Err(err) = self.check_move_type(&move_type) {
+            panic!("{}", err);
+        }
+        self.move_type = move_type;
+        self.move_type.set_size(self.state.len());
+    }
+
+    /// Keeps what the caches know about the state for a move about to be done or undone.
+    fn caches_before(&mut self, mov: &Move, undo: bool) {
+        self.positions.clear();
+        self.cache.update(&self.evaluation, &self.state, mov, undo);
+    }
+
+    /// Forgets what the caches know about the state.
+    fn clear_caches(&mut self) {
+        self.positions.clear();
+        self.cache.invalidate();
+    }
+
+    /// Computes the QAP swap deltas if the neighborhood about to be searched has swaps.
+    fn prepare_swap_deltas(&mut self, neighborhood: Option<usize>) {
+        let swaps = match self.move_type.select(neighborhood) {
+            MoveType::Swap { .. } => true,
+            MoveType::MultiNeighbor { move_types, .. } => move_types
+                .iter()
+                .any(|move_type| matches!(move_type, MoveType::Swap { .. })),
+            _ => false,
+        };
+        if swaps {
+            self.cache.prepare_swaps(&self.evaluation, &self.state);
+        }
+    }
+
+    /// Fails if the move type would move the fixed ends of the path.
+    pub(crate) fn check_move_type(&self, move_type: &MoveType) -> Result<(), String> {
+        if !self.evaluation.fixed_ends_kept_by(move_type) {
+            return Err("Move type doesn't keep the fixed ends of the path in place".to_string());
+        }
+        Ok(())
+    }
+
+    /// Fails if the current state or the move type don't keep the fixed ends of the
+    /// evaluation's path.
+    pub(crate) fn check_eval_type(&self, evaluation: &Evaluation) -> Result<(), String> {
+        evaluation.check_fixed_ends(&self.state)?;
+        if !evaluation.fixed_ends_kept_by(&self.move_type) {
+            return Err("Move type doesn't keep the fixed ends of the path in place".to_string());
+        }
+        Ok(())
+    }
+
     pub fn state(&self) -> &Vec<usize> {
         &self.state
     }
@@ -32,39 +149,92 @@ impl ArrayProblem {
     pub fn best_solution(&self) -> &Vec<usize> {
         &self.best_solution
     }
+
+    /// Gives the vehicle routes the given state stands for, fails if the evaluation has no
+    /// routes.
+    pub fn routes(&self, state: &State) -> Result<Vec<Vec<usize>>, String> {
+        self.evaluation.routes(state.permutation(self.state.len())?)
+    }
+
+    /// Gives the travel, lateness and visits of the given state, fails if the evaluation has
+    /// no time windows.
+    pub fn time_window_report(&self, state: &State) -> Result<TimeWindowReport, String> {
+        self.evaluation
+            .time_window_report(state.permutation(self.state.len())?)
+    }
 }
+
 impl Problem for ArrayProblem {
-    fn get_mov(&mut self) -> (usize, usize) {
+    fn get_mov(&mut self) -> Move {
         self.move_type.get_mov()
     }
 
-    fn get_all_mov(&mut self) -> Vec<(usize, usize)> {
+    fn get_all_mov(&mut self) -> Vec<Move> {
+        self.prepare_swap_deltas(None);
+        if self.candidate_count.is_some() {
+            if let Some(moves) = self
+                .move_type
+                .get_candidate_mov(&self.state, &self.candidates)
+            {
+                return moves;
+            }
+        }
         self.move_type.get_all_mov()
     }
 
-    fn do_mov(&mut self, indices: (usize, usize), move_type: Option<&MoveType>) {
-        match move_type {
-            Some(x) => x.do_move(&mut self.state, indices),
-            None => self.move_type.do_move(&mut self.state, indices),
+    fn iter_mov(
+        &mut self,
+        neighborhood: Option<usize>,
+        shuffle: bool,
+    ) -> Box<dyn Iterator<Item = Move>> {
+        self.prepare_swap_deltas(neighborhood);
+        let move_type = self.move_type.select(neighborhood);
+        if self.candidate_count.is_some() {
+            if let Some(moves) = move_type.get_candidate_mov(&self.state, &self.candidates) {
+                return Box::new(move_type.iter_list(moves, shuffle));
+            }
         }
+        Box::new(move_type.iter_mov(shuffle))
     }
 
-    fn delta_eval(&mut self, indices: (usize, usize), move_type: Option<&MoveType>) -> f64 {
-        match move_type {
-            Some(x) => self.evaluation.delta_eval(indices, x, &mut self.state),
-            None => self
-                .evaluation
-                .delta_eval(indices, &self.move_type, &mut self.state),
+    fn do_mov(&mut self, mov: &Move) {
+        self.caches_before(mov, false);
+        match mov {
+            Move::Custom(custom) => {
+                self.previous_state.clone_from(&self.state);
+                custom.do_move(&mut self.state);
+            }
+            _ => mov.do_move(&mut self.state),
+        }
+    }
+
+    fn undo_mov(&mut self, mov: &Move) {
+        self.caches_before(mov, true);
+        match mov {
+            Move::Custom(..) => self.state.clone_from(&self.previous_state),
+            _ => mov.undo_move(&mut self.state),
         }
     }
 
+    fn delta_eval(&mut self, mov: &Move) -> f64 {
+        self.cache.delta(&self.evaluation, mov, &mut self.state)
+    }
+
     fn eval(&self) -> f64 {
         self.evaluation.eval(&self.state)
     }
 
     fn reset(&mut self) {
-        self.state = (0..self.state.len()).collect();
-        self.best_solution = (0..self.state.len()).collect();
+        self.state = match &self.warm_start {
+            Some(order) => order.clone(),
+            None => {
+                let mut order = self.construction.build(&self.evaluation);
+                self.evaluation.place_fixed_ends(&mut order);
+                order
+            }
+        };
+        self.best_solution = self.state.clone();
+        self.clear_caches();
     }
 
     fn set_best(&mut self) {
@@ -77,19 +247,164 @@ impl Problem for ArrayProblem {
         hasher.finish()
     }
 
-    fn get_move_type(&self) -> &MoveType {
-        &self.move_type
+    fn neighborhood_count(&self) -> usize {
+        self.move_type.neighborhood_count()
     }
 
-    fn set_move_type(&mut self, move_type: MoveType) {
-        self.move_type = move_type;
+    fn set_seed(&mut self, seed: u64) {
+        self.move_type.set_seed(seed);
+        self.construction.set_seed(seed);
     }
 
-    fn set_eval_type(&mut self, eval_type: Evaluation) {
-        self.evaluation = eval_type;
+    fn get_state(&self) -> Option<State> {
+        Some(State::Permutation(self.state.clone()))
     }
 
-    fn set_seed(&mut self, seed: u64) {
-        self.move_type.set_seed(seed)
+    fn get_best_solution(&self) -> Option<State> {
+        Some(State::Permutation(self.best_solution.clone()))
+    }
+
+    fn set_state(&mut self, state: &State) -> Result<(), String> {
+        let order = state.permutation(self.state.len())?;
+        self.evaluation.check_fixed_ends(order)?;
+        self.state = order.to_vec();
+        self.clear_caches();
+        Ok(())
+    }
+
+    fn eval_state(&self, state: &State) -> Result<f64, String> {
+        Ok(self.evaluation.eval(state.permutation(self.state.len())?))
+    }
+
+    fn set_warm_start(&mut self, warm_start: Option<State>) -> Result<(), String> {
+        self.warm_start = match warm_start {
+            Some(state) => {
+                let order = state.permutation(self.state.len())?;
+                self.evaluation.check_fixed_ends(order)?;
+                Some(order.to_vec())
+            }
+            None => None,
+        };
+        Ok(())
+    }
+}
+
+impl ElementProblem for ArrayProblem {
+    fn element_count(&self) -> usize {
+        match self.move_type {
+            MoveType::Reverse { .. }
+            | MoveType::Swap { .. }
+            | MoveType::Tsp { .. }
+            | MoveType::Relocate { .. } => self.state.len(),
+            _ => 0,
+        }
+    }
+
+    /// Without candidate lists every other element is a candidate.
+    fn get_mov_around(&mut self, element: usize) -> Vec<Move> {
+        if self.positions.is_empty() {
+            self.positions = positions(&self.state);
+        }
+        let others: Vec<usize>;
+        let candidates = if self.candidate_count.is_some() {
+            &self.candidates[element]
+        } else {
+            others = (0..self.state.len()).filter(|i| *i != element).collect();
+            &others
+        };
+        self.move_type
+            .get_mov_around(&self.positions, element, candidates)
+            .unwrap_or_default()
+    }
+
+    fn touched_elements(&self, mov: &Move) -> Vec<usize> {
+        mov.endpoints(&self.state)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::local_search::{LocalSearch, TabuSearch};
+    use crate::problem::{ArrayProblem, Evaluation, MoveType, Problem};
+    use crate::termination::TerminationFunction;
+
+    fn qap(len: usize) -> Evaluation {
+        let matrix = |offset: usize| -> Vec<Vec<f64>> {
+            (0..len)
+                .map(|i| (0..len).map(|j| ((i * j + offset) % 7) as f64).collect())
+                .collect()
+        };
+        Evaluation::qap(matrix(1), matrix(3))
+    }
+
+    #[test]
+    fn set_move_type_test() {
+        let distance_matrix: Vec<Vec<f64>> = (0..5)
+            .map(|i: i32| (0..5).map(|j: i32| (i - j).abs() as f64).collect())
+            .collect();
+        let eval = Evaluation::tsp_path(distance_matrix.clone(), Some(0), Some(4));
+        let swap = MoveType::fixed_ends(MoveType::swap(Some(0)), true, true);
+        let mut problem = ArrayProblem::new(&swap, &eval);
+
+        // The new move type gets the size of the state, only the three inner positions move.
+        problem.set_move_type(MoveType::fixed_ends(MoveType::reverse(Some(0)), true, true));
+        assert_eq!(problem.get_all_mov().len(), 3);
+        assert!(problem
+            .check_move_type(&MoveType::reverse(Some(0)))
+            .is_err());
+
+        let other_start = Evaluation::tsp_path(distance_matrix.clone(), Some(1), None);
+        assert!(problem.check_eval_type(&other_start).is_err());
+        let same_start = Evaluation::tsp_path(distance_matrix, Some(0), None);
+        assert!(problem.check_eval_type(&same_start).is_ok());
+        problem.set_eval_type(same_start);
+    }
+
+    #[test]
+    #[should_panic(expected = "fixed ends")]
+    fn fixed_ends_move_type_test() {
+        let distance_matrix = vec![vec![0.0; 4]; 4];
+        let eval = Evaluation::tsp_path(distance_matrix, Some(0), None);
+        let swap = MoveType::fixed_ends(MoveType::swap(Some(0)), true, false);
+        let mut problem = ArrayProblem::new(&swap, &eval);
+        problem.set_move_type(MoveType::swap(Some(0)));
+    }
+
+    #[test]
+    fn tabu_swap_deltas_test() {
+        let array_problem = Arc::new(Mutex::new(ArrayProblem::new(
+            &MoveType::swap(Some(0)),
+            &qap(12),
+        )));
+        let problem: Arc<Mutex<dyn Problem>> = array_problem.clone();
+        let termination = TerminationFunction::max_iterations(20);
+        let mut tabu = TabuSearch::new(&problem, &termination, true, Some(5));
+        let data = tabu.run(false, None).unwrap();
+        let (_, _, current, iterations) = *data.last().unwrap();
+        assert_eq!(iterations, 20);
+
+        // The deltas left by the search, and kept over further probes and swaps, match a full
+        // evaluation.
+        let mut array_problem = array_problem.lock().unwrap();
+        assert!((array_problem.eval() - current).abs() < 1e-9);
+        for step in 0..5 {
+            let moves = array_problem.get_all_mov();
+            for mov in &moves {
+                let score = array_problem.eval();
+                let delta = array_problem.delta_eval(mov);
+                array_problem.do_mov(mov);
+                assert!((array_problem.eval() - score - delta).abs() < 1e-9);
+                array_problem.undo_mov(mov);
+            }
+            array_problem.do_mov(&moves[step * 7]);
+        }
+        for mov in array_problem.get_all_mov() {
+            let score = array_problem.eval();
+            let delta = array_problem.delta_eval(&mov);
+            array_problem.do_mov(&mov);
+            assert!((array_problem.eval() - score - delta).abs() < 1e-9);
+            array_problem.undo_mov(&mov);
+        }
     }
 }
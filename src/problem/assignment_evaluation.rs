@@ -0,0 +1,243 @@
+use super::Move;
+
+/// Evaluation of an assignment of values, used by an
+/// [AssignmentProblem](super::AssignmentProblem).
+#[derive(Clone)]
+pub enum AssignmentEvaluation {
+    GraphColoring {
+        weight_matrix: Vec<Vec<f64>>,
+        colors: usize,
+    },
+    GeneralizedAssignment {
+        costs: Vec<Vec<f64>>,
+        resources: Vec<Vec<f64>>,
+        capacities: Vec<f64>,
+        penalty: f64,
+    },
+    GroupBins {
+        weights: Vec<f64>,
+        max_fill: f64,
+        bins: usize,
+        penalty: f64,
+    },
+}
+impl AssignmentEvaluation {
+    /// Graph coloring to be minimized on an assignment of colors: the summed weight of all edges
+    /// between equally colored nodes. Only the upper triangle of the weight matrix is used.
+    pub fn graph_coloring(weight_matrix: Vec<Vec<f64>>, colors: usize) -> AssignmentEvaluation {
+        AssignmentEvaluation::GraphColoring {
+            weight_matrix,
+            colors,
+        }
+    }
+    /// Generalized assignment to be minimized on an assignment of agents: the summed cost
+    /// `costs[item][agent]` plus `penalty` times the resources used above the capacity of every
+    /// agent, where an item uses `resources[item][agent]`. The default penalty is the sum of all
+    /// costs.
+    pub fn generalized_assignment(
+        costs: Vec<Vec<f64>>,
+        resources: Vec<Vec<f64>>,
+        capacities: Vec<f64>,
+        penalty: Option<f64>,
+    ) -> AssignmentEvaluation {
+        let penalty = penalty.unwrap_or(costs.iter().flatten().sum());
+        AssignmentEvaluation::GeneralizedAssignment {
+            costs,
+            resources,
+            capacities,
+            penalty,
+        }
+    }
+    /// Bin packing to be minimized on an assignment of bins: the number of used bins plus
+    /// `penalty` times the weight above `max_fill` of every bin. By default there are as many
+    /// bins as items and the penalty is the number of items.
+    pub fn group_bins(
+        weights: Vec<f64>,
+        max_fill: f64,
+        bins: Option<usize>,
+        penalty: Option<f64>,
+    ) -> AssignmentEvaluation {
+        let bins = bins.unwrap_or(weights.len());
+        let penalty = penalty.unwrap_or(weights.len() as f64);
+        AssignmentEvaluation::GroupBins {
+            weights,
+            max_fill,
+            bins,
+            penalty,
+        }
+    }
+
+    /// Number of values every position of an assignment can take.
+    pub(crate) fn domain(&self) -> usize {
+        match self {
+            AssignmentEvaluation::GraphColoring { colors, .. } => *colors,
+            AssignmentEvaluation::GeneralizedAssignment { capacities, .. } => capacities.len(),
+            AssignmentEvaluation::GroupBins { bins, .. } => *bins,
+        }
+    }
+
+    /// Change in score if the move would be performed on the assignment.
+    pub(crate) fn delta_eval(&self, mov: &Move, assignment: &mut [usize]) -> f64 {
+        match *mov {
+            Move::Assign(index, value) => self.delta_assign(index, value, assignment),
+            Move::Swap(i, j) => {
+                let (first, sec) = (assignment[i], assignment[j]);
+                let delta = self.delta_assign(i, sec, assignment);
+                assignment[i] = sec;
+                let delta = delta + self.delta_assign(j, first, assignment);
+                assignment[i] = first;
+                delta
+            }
+            Move::Custom(ref custom) => {
+                let mut next = assignment.to_vec();
+                custom.do_move(&mut next);
+                self.eval(&next) - self.eval(assignment)
+            }
+            _ => {
+                let first = self.eval(assignment);
+                mov.do_move(assignment);
+                let sec = self.eval(assignment);
+                mov.undo_move(assignment);
+                sec - first
+            }
+        }
+    }
+
+    /// Change in score of an assignment when position `index` gets `value`.
+    pub(crate) fn delta_assign(&self, index: usize, value: usize, assignment: &[usize]) -> f64 {
+        let old = assignment[index];
+        if old == value {
+            return 0.0;
+        }
+        match self {
+            AssignmentEvaluation::GraphColoring { weight_matrix, .. } => {
+                let mut delta = 0.0;
+                for (j, color) in assignment.iter().enumerate() {
+                    if j == index {
+                        continue;
+                    }
+                    let weight = weight_matrix[index.min(j)][index.max(j)];
+                    if *color == value {
+                        delta += weight;
+                    } else if *color == old {
+                        delta -= weight;
+                    }
+                }
+                delta
+            }
+            AssignmentEvaluation::GeneralizedAssignment {
+                costs,
+                resources,
+                capacities,
+                penalty,
+            } => {
+                let mut old_load = 0.0;
+                let mut new_load = 0.0;
+                for (item, agent) in assignment.iter().enumerate() {
+                    if *agent == old {
+                        old_load += resources[item][old];
+                    } else if *agent == value {
+                        new_load += resources[item][value];
+                    }
+                }
+                let overload = |load: f64, agent: usize| (load - capacities[agent]).max(0.0);
+                costs[index][value] - costs[index][old]
+                    + penalty
+                        * (overload(old_load - resources[index][old], old)
+                            - overload(old_load, old)
+                            + overload(new_load + resources[index][value], value)
+                            - overload(new_load, value))
+            }
+            AssignmentEvaluation::GroupBins {
+                weights,
+                max_fill,
+                penalty,
+                ..
+            } => {
+                let mut old_fill = 0.0;
+                let mut old_count = 0;
+                let mut new_fill = 0.0;
+                let mut new_count = 0;
+                for (item, bin) in assignment.iter().enumerate() {
+                    if *bin == old {
+                        old_fill += weights[item];
+                        old_count += 1;
+                    } else if *bin == value {
+                        new_fill += weights[item];
+                        new_count += 1;
+                    }
+                }
+                let overflow = |fill: f64| (fill - max_fill).max(0.0);
+                let mut delta = penalty
+                    * (overflow(old_fill - weights[index]) - overflow(old_fill)
+                        + overflow(new_fill + weights[index])
+                        - overflow(new_fill));
+                if old_count == 1 {
+                    delta -= 1.0;
+                }
+                if new_count == 0 {
+                    delta += 1.0;
+                }
+                delta
+            }
+        }
+    }
+
+    /// Calculates the score of an assignment.
+    pub(crate) fn eval(&self, assignment: &[usize]) -> f64 {
+        match self {
+            AssignmentEvaluation::GraphColoring { weight_matrix, .. } => {
+                let mut score = 0.0;
+                for i in 0..assignment.len() {
+                    for j in (i + 1)..assignment.len() {
+                        if assignment[i] == assignment[j] {
+                            score += weight_matrix[i][j];
+                        }
+                    }
+                }
+                score
+            }
+            AssignmentEvaluation::GeneralizedAssignment {
+                costs,
+                resources,
+                capacities,
+                penalty,
+            } => {
+                let mut score = 0.0;
+                let mut loads = vec![0.0; capacities.len()];
+                for (item, agent) in assignment.iter().enumerate() {
+                    score += costs[item][*agent];
+                    loads[*agent] += resources[item][*agent];
+                }
+                for (load, capacity) in loads.iter().zip(capacities) {
+                    score += penalty * (load - capacity).max(0.0);
+                }
+                score
+            }
+            AssignmentEvaluation::GroupBins {
+                weights,
+                max_fill,
+                bins,
+                penalty,
+            } => {
+                let mut score = 0.0;
+                let mut fills = vec![None; *bins];
+                for (item, bin) in assignment.iter().enumerate() {
+                    *fills[*bin].get_or_insert(0.0) += weights[item];
+                }
+                for fill in fills.iter().flatten() {
+                    score += 1.0 + penalty * (fill - max_fill).max(0.0);
+                }
+                score
+            }
+        }
+    }
+
+    pub(crate) fn length(&self) -> usize {
+        match self {
+            AssignmentEvaluation::GraphColoring { weight_matrix, .. } => weight_matrix.len(),
+            AssignmentEvaluation::GeneralizedAssignment { costs, .. } => costs.len(),
+            AssignmentEvaluation::GroupBins { weights, .. } => weights.len(),
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use super::{AssignmentEvaluation, Move, MoveType, Problem, State};
+
+/// Problem where every position takes a value of a finite domain, like colors, agents or bins.
+pub struct AssignmentProblem {
+    state: Vec<usize>,
+    best_solution: Vec<usize>,
+    initial_assignment: Vec<usize>,
+    warm_start: Option<Vec<usize>>,
+    move_type: MoveType,
+    evaluation: AssignmentEvaluation,
+    replaced_value: usize,
+    previous_state: Vec<usize>,
+}
+impl AssignmentProblem {
+    /// Creates a new assignment problem, the domain is given by the evaluation.
+    ///
+    /// # Arguments
+    ///
+    /// * `initial_assignment`: assignment used at the start and after every reset, every
+    ///   position gets value 0 if not given.
+    pub fn new(
+        move_type: &MoveType,
+        evaluation: &AssignmentEvaluation,
+        initial_assignment: Option<Vec<usize>>,
+    ) -> Self {
+        let len = evaluation.length();
+        let initial_assignment = initial_assignment.unwrap_or(vec![0; len]);
+        assert_eq!(
+            initial_assignment.len(),
+            len,
+            "Initial assignment has the wrong length"
+        );
+        assert!(
+            initial_assignment.iter().all(|x| *x < evaluation.domain()),
+            "Initial assignment contains a value outside the domain"
+        );
+        let mut mov = move_type.clone();
+        mov.set_size(len);
+        mov.set_domain(evaluation.domain());
+        AssignmentProblem {
+            state: initial_assignment.clone(),
+            best_solution: initial_assignment.clone(),
+            initial_assignment,
+            warm_start: None,
+            move_type: mov,
+            evaluation: evaluation.clone(),
+            replaced_value: 0,
+            previous_state: vec![],
+        }
+    }
+
+    pub fn state(&self) -> &Vec<usize> {
+        &self.state
+    }
+
+    pub fn best_solution(&self) -> &Vec<usize> {
+        &self.best_solution
+    }
+
+    /// Sets the evaluation, which also gives the domain of the move type.
+    pub fn set_eval_type(&mut self, eval_type: AssignmentEvaluation) {
+        self.move_type.set_domain(eval_type.domain());
+        self.evaluation = eval_type;
+    }
+
+    pub fn set_move_type(&mut self, move_type: MoveType) {
+        self.move_type = move_type;
+        self.move_type.set_size(self.state.len());
+        self.move_type.set_domain(self.evaluation.domain());
+    }
+
+    fn is_noop(&self, mov: &Move) -> bool {
+        is_noop(&self.state, mov)
+    }
+}
+impl Problem for AssignmentProblem {
+    fn get_mov(&mut self) -> Move {
+        let mut mov = self.move_type.get_mov();
+        while self.is_noop(&mov) && self.evaluation.domain() > 1 {
+            mov = self.move_type.get_mov();
+        }
+        mov
+    }
+
+    fn get_all_mov(&mut self) -> Vec<Move> {
+        self.move_type
+            .get_all_mov()
+            .into_iter()
+            .filter(|mov| !self.is_noop(mov))
+            .collect()
+    }
+
+    fn iter_mov(
+        &mut self,
+        neighborhood: Option<usize>,
+        shuffle: bool,
+    ) -> Box<dyn Iterator<Item = Move>> {
+        let state = self.state.clone();
+        let moves = self.move_type.select(neighborhood).iter_mov(shuffle);
+        Box::new(moves.filter(move |mov| !is_noop(&state, mov)))
+    }
+
+    fn do_mov(&mut self, mov: &Move) {
+        match mov {
+            Move::Assign(index, value) => {
+                self.replaced_value = self.state[*index];
+                self.state[*index] = *value;
+            }
+            Move::Custom(custom) => {
+                self.previous_state.clone_from(&self.state);
+                custom.do_move(&mut self.state);
+            }
+            _ => mov.do_move(&mut self.state),
+        }
+    }
+
+    fn undo_mov(&mut self, mov: &Move) {
+        match mov {
+            Move::Assign(index, _) => self.state[*index] = self.replaced_value,
+            Move::Custom(..) => self.state.clone_from(&self.previous_state),
+            _ => mov.undo_move(&mut self.state),
+        }
+    }
+
+    fn delta_eval(&mut self, mov: &Move) -> f64 {
+        self.evaluation.delta_eval(mov, &mut self.state)
+    }
+
+    fn eval(&self) -> f64 {
+        self.evaluation.eval(&self.state)
+    }
+
+    fn reset(&mut self) {
+        self.state = match &self.warm_start {
+            Some(values) => values.clone(),
+            None => self.initial_assignment.clone(),
+        };
+        self.best_solution = self.state.clone();
+    }
+
+    fn set_best(&mut self) {
+        self.best_solution = self.state.to_vec();
+    }
+
+    fn hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.state.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn neighborhood_count(&self) -> usize {
+        self.move_type.neighborhood_count()
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.move_type.set_seed(seed)
+    }
+
+    fn get_state(&self) -> Option<State> {
+        Some(State::Assignment(self.state.clone()))
+    }
+
+    fn get_best_solution(&self) -> Option<State> {
+        Some(State::Assignment(self.best_solution.clone()))
+    }
+
+    fn set_state(&mut self, state: &State) -> Result<(), String> {
+        self.state = state
+            .assignment(self.state.len(), self.evaluation.domain())?
+            .to_vec();
+        Ok(())
+    }
+
+    fn eval_state(&self, state: &State) -> Result<f64, String> {
+        let values = state.assignment(self.state.len(), self.evaluation.domain())?;
+        Ok(self.evaluation.eval(values))
+    }
+
+    fn set_warm_start(&mut self, warm_start: Option<State>) -> Result<(), String> {
+        self.warm_start = match warm_start {
+            Some(state) => Some(
+                state
+                    .assignment(self.state.len(), self.evaluation.domain())?
+                    .to_vec(),
+            ),
+            None => None,
+        };
+        Ok(())
+    }
+}
+
+/// Whether the move assigns a position the value it already has.
+fn is_noop(state: &[usize], mov: &Move) -> bool {
+    match *mov {
+        Move::Assign(index, value) => state[index] == value,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::local_search::{LocalSearch, TabuSearch};
+    use crate::problem::{AssignmentEvaluation, AssignmentProblem, Move, MoveType, Problem};
+    use crate::termination::TerminationFunction;
+
+    fn evaluations() -> Vec<AssignmentEvaluation> {
+        vec![
+            AssignmentEvaluation::graph_coloring(
+                vec![
+                    vec![0.0, 1.0, 1.0, 0.0, 1.0],
+                    vec![1.0, 0.0, 1.0, 1.0, 0.0],
+                    vec![1.0, 1.0, 0.0, 1.0, 0.0],
+                    vec![0.0, 1.0, 1.0, 0.0, 1.0],
+                    vec![1.0, 0.0, 0.0, 1.0, 0.0],
+                ],
+                3,
+            ),
+            AssignmentEvaluation::generalized_assignment(
+                vec![
+                    vec![4.0, 2.0, 5.0],
+                    vec![3.0, 6.0, 1.0],
+                    vec![2.0, 2.0, 7.0],
+                    vec![5.0, 1.0, 3.0],
+                    vec![6.0, 4.0, 2.0],
+                ],
+                vec![
+                    vec![3.0, 2.0, 4.0],
+                    vec![2.0, 3.0, 1.0],
+                    vec![4.0, 2.0, 3.0],
+                    vec![1.0, 4.0, 2.0],
+                    vec![3.0, 3.0, 3.0],
+                ],
+                vec![5.0, 6.0, 4.0],
+                None,
+            ),
+            AssignmentEvaluation::group_bins(vec![4.0, 6.0, 3.0, 7.0, 2.0], 10.0, Some(3), None),
+        ]
+    }
+
+    #[test]
+    fn delta_eval_test() {
+        let moves = vec![
+            Move::Assign(0, 1),
+            Move::Assign(3, 2),
+            Move::Swap(0, 3),
+            Move::Assign(4, 1),
+            Move::Assign(2, 2),
+            Move::Swap(1, 4),
+            Move::Assign(1, 1),
+            Move::Reverse(0, 4),
+        ];
+        for eval in evaluations() {
+            let mut problem = AssignmentProblem::new(&MoveType::assign(Some(0)), &eval, None);
+            for mov in &moves {
+                let before = problem.eval();
+                let delta = problem.delta_eval(mov);
+                problem.do_mov(mov);
+                let after = problem.eval();
+                assert!((delta - (after - before)).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn moves_test() {
+        let eval = evaluations().remove(0);
+        let mut problem =
+            AssignmentProblem::new(&MoveType::assign(Some(0)), &eval, Some(vec![0, 1, 2, 0, 1]));
+        let moves = problem.get_all_mov();
+        assert_eq!(moves.len(), 10);
+        assert!(!moves.contains(&Move::Assign(2, 2)));
+        for _ in 0..20 {
+            let mov = problem.get_mov();
+            assert!(moves.contains(&mov));
+        }
+
+        problem.do_mov(&Move::Assign(3, 2));
+        problem.undo_mov(&Move::Assign(3, 2));
+        assert_eq!(*problem.state(), [0, 1, 2, 0, 1]);
+
+        problem.do_mov(&Move::Assign(3, 2));
+        problem.reset();
+        assert_eq!(*problem.state(), [0, 1, 2, 0, 1]);
+    }
+
+    #[test]
+    fn graph_coloring_tabu_search_test() {
+        let eval = evaluations().remove(0);
+        let problem: Arc<Mutex<dyn Problem>> = Arc::new(Mutex::new(AssignmentProblem::new(
+            &MoveType::assign(Some(0)),
+            &eval,
+            None,
+        )));
+        let termination = TerminationFunction::max_iterations(50);
+
+        let mut sim = TabuSearch::new(&problem, &termination, true, None);
+        let data = sim.run(false, None).unwrap().last().unwrap().1;
+        assert_eq!(data, 0.0);
+    }
+}
@@ -0,0 +1,111 @@
+/// Evaluation of a string of bits, used by a [BinaryProblem](super::BinaryProblem).
+#[derive(Clone)]
+pub enum BinaryEvaluation {
+    Knapsack {
+        values: Vec<f64>,
+        weights: Vec<f64>,
+        capacity: f64,
+        penalty: f64,
+    },
+    MaxCut {
+        weight_matrix: Vec<Vec<f64>>,
+    },
+    Qubo {
+        q_matrix: Vec<Vec<f64>>,
+    },
+}
+impl BinaryEvaluation {
+    /// Knapsack to be maximized on a binary state: the total value of the selected items minus
+    /// `penalty` times the weight exceeding the capacity. The default penalty is the sum of all
+    /// values, which makes every solution that is at least one unit overweight worse than any
+    /// feasible one.
+    pub fn knapsack(
+        values: Vec<f64>,
+        weights: Vec<f64>,
+        capacity: f64,
+        penalty: Option<f64>,
+    ) -> BinaryEvaluation {
+        let penalty = penalty.unwrap_or(values.iter().sum());
+        BinaryEvaluation::Knapsack {
+            values,
+            weights,
+            capacity,
+            penalty,
+        }
+    }
+    /// Max-cut to be maximized on a binary state: the summed weight of all edges between both
+    /// partitions. Only the upper triangle of the weight matrix is used.
+    pub fn max_cut(weight_matrix: Vec<Vec<f64>>) -> BinaryEvaluation {
+        BinaryEvaluation::MaxCut { weight_matrix }
+    }
+    /// Quadratic unconstrained binary optimization on a binary state: `x^T Q x`.
+    pub fn qubo(q_matrix: Vec<Vec<f64>>) -> BinaryEvaluation {
+        BinaryEvaluation::Qubo { q_matrix }
+    }
+
+    /// Calculates the score of a binary state.
+    pub(crate) fn eval(&self, bits: &[bool]) -> f64 {
+        match self {
+            BinaryEvaluation::Knapsack {
+                values,
+                weights,
+                capacity,
+                penalty,
+            } => {
+                let mut value = 0.0;
+                let mut load = 0.0;
+                for i in 0..bits.len() {
+                    if bits[i] {
+                        value += values[i];
+                        load += weights[i];
+                    }
+                }
+                knapsack_score(value, load, *capacity, *penalty)
+            }
+            BinaryEvaluation::MaxCut { weight_matrix } => {
+                let mut score = 0.0;
+                for i in 0..bits.len() {
+                    for j in (i + 1)..bits.len() {
+                        if bits[i] != bits[j] {
+                            score += weight_matrix[i][j];
+                        }
+                    }
+                }
+                score
+            }
+            BinaryEvaluation::Qubo { q_matrix } => {
+                let mut score = 0.0;
+                for i in 0..bits.len() {
+                    for j in 0..bits.len() {
+                        if bits[i] && bits[j] {
+                            score += q_matrix[i][j];
+                        }
+                    }
+                }
+                score
+            }
+        }
+    }
+
+    /// Weight of the interaction between bits `i` and `j` of a quadratic binary evaluation.
+    pub(crate) fn coupling(&self, i: usize, j: usize) -> f64 {
+        match self {
+            BinaryEvaluation::MaxCut { weight_matrix } => weight_matrix[i.min(j)][i.max(j)],
+            BinaryEvaluation::Qubo { q_matrix } => q_matrix[i][j] + q_matrix[j][i],
+            BinaryEvaluation::Knapsack { .. } => 0.0,
+        }
+    }
+
+    pub(crate) fn length(&self) -> usize {
+        match self {
+            BinaryEvaluation::Knapsack { values, .. } => values.len(),
+            BinaryEvaluation::MaxCut { weight_matrix } => weight_matrix.len(),
+            BinaryEvaluation::Qubo { q_matrix } => q_matrix.len(),
+        }
+    }
+}
+
+/// Total value of a knapsack minus the penalty for every unit of weight above the capacity.
+pub(crate) fn knapsack_score(value: f64, load: f64, capacity: f64, penalty: f64) -> f64 {
+    value - penalty * (load - capacity).max(0.0)
+}
@@ -0,0 +1,398 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use super::binary_evaluation::knapsack_score;
+use super::{BinaryEvaluation, Move, MoveType, Problem, State};
+
+/// Problem on a string of bits, for models like knapsack, max-cut and QUBO.
+///
+/// Flip deltas are O(1): the problem caches, for every bit, the summed coupling with all bits
+/// that are set (or the load and value for a knapsack) and updates it whenever a move is done.
+pub struct BinaryProblem {
+    state: Vec<bool>,
+    best_solution: Vec<bool>,
+    warm_start: Option<Vec<bool>>,
+    move_type: MoveType,
+    evaluation: BinaryEvaluation,
+    field: Vec<f64>,
+    degree: Vec<f64>,
+    load: f64,
+    value: f64,
+}
+impl BinaryProblem {
+    pub fn new(move_type: &MoveType, evaluation: &BinaryEvaluation) -> Self {
+        let len = evaluation.length();
+        let mut mov = move_type.clone();
+        mov.set_size(len);
+        let mut binary_problem = BinaryProblem {
+            state: vec![false; len],
+            best_solution: vec![false; len],
+            warm_start: None,
+            move_type: mov,
+            evaluation: evaluation.clone(),
+            field: vec![],
+            degree: vec![],
+            load: 0.0,
+            value: 0.0,
+        };
+        binary_problem.init_cache();
+        binary_problem
+    }
+
+    pub fn state(&self) -> &Vec<bool> {
+        &self.state
+    }
+
+    pub fn best_solution(&self) -> &Vec<bool> {
+        &self.best_solution
+    }
+
+    /// Sets the evaluation and computes the cache again.
+    pub fn set_eval_type(&mut self, eval_type: BinaryEvaluation) {
+        self.evaluation = eval_type;
+        self.init_cache();
+    }
+
+    pub fn set_move_type(&mut self, move_type: MoveType) {
+        self.move_type = move_type;
+        self.move_type.set_size(self.state.len());
+    }
+
+    /// Recalculates all cached values from the current state.
+    fn init_cache(&mut self) {
+        let len = self.state.len();
+        self.field = vec![0.0; len];
+        self.degree = vec![0.0; len];
+        self.load = 0.0;
+        self.value = 0.0;
+        match &self.evaluation {
+            BinaryEvaluation::Knapsack {
+                values, weights, ..
+            } => {
+                for (i, _) in self.state.iter().enumerate().filter(|(_, bit)| **bit) {
+                    self.load += weights[i];
+                    self.value += values[i];
+                }
+            }
+            BinaryEvaluation::MaxCut { .. } | BinaryEvaluation::Qubo { .. } => {
+                for i in 0..len {
+                    for j in (0..len).filter(|j| *j != i) {
+                        let coupling = self.evaluation.coupling(i, j);
+                        self.degree[i] += coupling;
+                        if self.state[j] {
+                            self.field[i] += coupling;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// +1.0 if flipping bit `i` sets it, -1.0 if it clears it.
+    fn sign(&self, i: usize) -> f64 {
+        if self.state[i] {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    /// Change in score when flipping the bits in `indices`, which must be distinct.
+    fn flip_delta(&self, indices: &[usize]) -> f64 {
+        match &self.evaluation {
+            BinaryEvaluation::Knapsack {
+                values,
+                weights,
+                capacity,
+                penalty,
+            } => {
+                let mut value = self.value;
+                let mut load = self.load;
+                for &i in indices {
+                    value += self.sign(i) * values[i];
+                    load += self.sign(i) * weights[i];
+                }
+                knapsack_score(value, load, *capacity, *penalty)
+                    - knapsack_score(self.value, self.load, *capacity, *penalty)
+            }
+            BinaryEvaluation::MaxCut { .. } => {
+                let mut delta = 0.0;
+                for (k, &i) in indices.iter().enumerate() {
+                    delta += self.sign(i) * (self.degree[i] - 2.0 * self.field[i]);
+                    for &j in &indices[..k] {
+                        delta -= 2.0 * self.evaluation.coupling(i, j) * self.sign(i) * self.sign(j);
+                    }
+                }
+                delta
+            }
+            BinaryEvaluation::Qubo { q_matrix } => {
+                let mut delta = 0.0;
+                for (k, &i) in indices.iter().enumerate() {
+                    delta += self.sign(i) * (q_matrix[i][i] + self.field[i]);
+                    for &j in &indices[..k] {
+                        delta += self.evaluation.coupling(i, j) * self.sign(i) * self.sign(j);
+                    }
+                }
+                delta
+            }
+        }
+    }
+
+    /// Flips bit `i` and updates the cache.
+    fn flip(&mut self, i: usize) {
+        let sign = self.sign(i);
+        match &self.evaluation {
+            BinaryEvaluation::Knapsack {
+                values, weights, ..
+            } => {
+                self.value += sign * values[i];
+                self.load += sign * weights[i];
+            }
+            _ => {
+                for j in (0..self.state.len()).filter(|j| *j != i) {
+                    self.field[j] += sign * self.evaluation.coupling(i, j);
+                }
+            }
+        }
+        self.state[i] = !self.state[i];
+    }
+
+    /// Makes the state equal to `target` by flipping every bit that differs.
+    fn flip_to(&mut self, target: &[bool]) {
+        for (i, bit) in target.iter().enumerate() {
+            if self.state[i] != *bit {
+                self.flip(i);
+            }
+        }
+    }
+}
+impl Problem for BinaryProblem {
+    fn get_mov(&mut self) -> Move {
+        self.move_type.get_mov()
+    }
+
+    fn get_all_mov(&mut self) -> Vec<Move> {
+        self.move_type.get_all_mov()
+    }
+
+    fn iter_mov(
+        &mut self,
+        neighborhood: Option<usize>,
+        shuffle: bool,
+    ) -> Box<dyn Iterator<Item = Move>> {
+        Box::new(self.move_type.select(neighborhood).iter_mov(shuffle))
+    }
+
+    fn do_mov(&mut self, mov: &Move) {
+        match *mov {
+            Move::Flip(i) => self.flip(i),
+            Move::FlipPair(i, j) => {
+                self.flip(i);
+                self.flip(j);
+            }
+            _ => {
+                let mut target = self.state.clone();
+                mov.do_move(&mut target);
+                self.flip_to(&target);
+            }
+        }
+    }
+
+    fn undo_mov(&mut self, mov: &Move) {
+        match *mov {
+            Move::Flip(..) | Move::FlipPair(..) => self.do_mov(mov),
+            _ => {
+                let mut target = self.state.clone();
+                mov.undo_move(&mut target);
+                self.flip_to(&target);
+            }
+        }
+    }
+
+    fn delta_eval(&mut self, mov: &Move) -> f64 {
+        match *mov {
+            Move::Flip(i) => self.flip_delta(&[i]),
+            Move::FlipPair(i, j) => self.flip_delta(&[i, j]),
+            _ => {
+                let first = self.evaluation.eval(&self.state);
+                mov.do_move(&mut self.state);
+                let sec = self.evaluation.eval(&self.state);
+                mov.undo_move(&mut self.state);
+                sec - first
+            }
+        }
+    }
+
+    fn eval(&self) -> f64 {
+        self.evaluation.eval(&self.state)
+    }
+
+    fn reset(&mut self) {
+        self.state = match &self.warm_start {
+            Some(bits) => bits.clone(),
+            None => vec![false; self.state.len()],
+        };
+        self.best_solution = self.state.clone();
+        self.init_cache();
+    }
+
+    fn set_best(&mut self) {
+        self.best_solution = self.state.to_vec();
+    }
+
+    fn hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.state.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn neighborhood_count(&self) -> usize {
+        self.move_type.neighborhood_count()
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.move_type.set_seed(seed)
+    }
+
+    fn get_state(&self) -> Option<State> {
+        Some(State::Binary(self.state.clone()))
+    }
+
+    fn get_best_solution(&self) -> Option<State> {
+        Some(State::Binary(self.best_solution.clone()))
+    }
+
+    fn set_state(&mut self, state: &State) -> Result<(), String> {
+        self.state = state.binary(self.state.len())?.to_vec();
+        self.init_cache();
+        Ok(())
+    }
+
+    fn eval_state(&self, state: &State) -> Result<f64, String> {
+        Ok(self.evaluation.eval(state.binary(self.state.len())?))
+    }
+    fn set_warm_start(&mut self, warm_start: Option<State>) -> Result<(), String> {
+        self.warm_start = match warm_start {
+            Some(state) => Some(state.binary(self.state.len())?.to_vec()),
+            None => None,
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::local_search::{LocalSearch, SimulatedAnnealing, TabuSearch};
+    use crate::problem::{BinaryEvaluation, BinaryProblem, Move, MoveType, Problem};
+    use crate::simulated_annealing::{CoolingFunction, IterationsTemperature};
+    use crate::termination::TerminationFunction;
+
+    fn evaluations() -> Vec<BinaryEvaluation> {
+        vec![
+            BinaryEvaluation::knapsack(
+                vec![4.0, 2.0, 10.0, 1.0, 2.0],
+                vec![12.0, 2.0, 4.0, 1.0, 1.0],
+                15.0,
+                None,
+            ),
+            BinaryEvaluation::max_cut(vec![
+                vec![0.0, 1.0, 3.0, 0.0, 2.0],
+                vec![1.0, 0.0, 1.0, 4.0, 0.0],
+                vec![3.0, 1.0, 0.0, 2.0, 1.0],
+                vec![0.0, 4.0, 2.0, 0.0, 5.0],
+                vec![2.0, 0.0, 1.0, 5.0, 0.0],
+            ]),
+            BinaryEvaluation::qubo(vec![
+                vec![-3.0, 2.0, 0.0, 1.0, 0.0],
+                vec![0.0, -2.0, 1.0, 0.0, 4.0],
+                vec![1.0, 0.0, -4.0, 2.0, 0.0],
+                vec![0.0, 3.0, 0.0, -1.0, 2.0],
+                vec![2.0, 0.0, 1.0, 0.0, -5.0],
+            ]),
+        ]
+    }
+
+    #[test]
+    fn delta_eval_test() {
+        let moves = vec![
+            Move::Flip(0),
+            Move::FlipPair(1, 3),
+            Move::Flip(2),
+            Move::FlipPair(0, 4),
+            Move::Swap(2, 4),
+            Move::FlipPair(2, 3),
+            Move::Reverse(0, 3),
+        ];
+        for eval in evaluations() {
+            let mut problem = BinaryProblem::new(&MoveType::flip(Some(0)), &eval);
+            for mov in &moves {
+                let before = problem.eval();
+                let delta = problem.delta_eval(mov);
+                problem.do_mov(mov);
+                let after = problem.eval();
+                assert!((delta - (after - before)).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn undo_mov_test() {
+        for eval in evaluations() {
+            let mut problem = BinaryProblem::new(&MoveType::flip_pair(Some(0)), &eval);
+            problem.do_mov(&Move::FlipPair(0, 2));
+            let score = problem.eval();
+            let delta = problem.delta_eval(&Move::Flip(1));
+            for mov in [Move::Flip(3), Move::FlipPair(1, 4), Move::Reverse(0, 4)] {
+                problem.do_mov(&mov);
+                problem.undo_mov(&mov);
+            }
+            assert_eq!(*problem.state(), [true, false, true, false, false]);
+            assert_eq!(problem.eval(), score);
+            assert!((problem.delta_eval(&Move::Flip(1)) - delta).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn knapsack_simulated_annealing_test() {
+        let eval = BinaryEvaluation::knapsack(
+            vec![4.0, 2.0, 10.0, 1.0, 2.0],
+            vec![12.0, 2.0, 4.0, 1.0, 1.0],
+            15.0,
+            None,
+        );
+        let problem: Arc<Mutex<dyn Problem>> = Arc::new(Mutex::new(BinaryProblem::new(
+            &MoveType::flip(Some(0)),
+            &eval,
+        )));
+        let cooling = CoolingFunction::geometric_cooling(0.75);
+        let termination = TerminationFunction::min_temp(2);
+        let iter = IterationsTemperature::const_iter_temp(100);
+
+        let mut sim = SimulatedAnnealing::new(20, false, &problem, &termination, &cooling, &iter);
+        let data = sim.run(false, None).unwrap().last().unwrap().1;
+        assert_eq!(data, 15.0);
+    }
+
+    #[test]
+    fn max_cut_tabu_search_test() {
+        let eval = BinaryEvaluation::max_cut(vec![
+            vec![0.0, 1.0, 1.0, 0.0],
+            vec![1.0, 0.0, 0.0, 1.0],
+            vec![1.0, 0.0, 0.0, 1.0],
+            vec![0.0, 1.0, 1.0, 0.0],
+        ]);
+        let problem: Arc<Mutex<dyn Problem>> = Arc::new(Mutex::new(BinaryProblem::new(
+            &MoveType::flip(Some(0)),
+            &eval,
+        )));
+        let termination = TerminationFunction::max_iterations(20);
+
+        let mut sim = TabuSearch::new(&problem, &termination, false, None);
+        let data = sim.run(false, None).unwrap().last().unwrap().1;
+        assert_eq!(data, 4.0);
+    }
+}
@@ -0,0 +1,431 @@
+use rand::{rngs::SmallRng, seq::SliceRandom, SeedableRng};
+
+use super::Evaluation;
+
+/// Heuristic that builds the order an [ArrayProblem](super::ArrayProblem) starts from and
+/// returns to on every reset.
+#[derive(Clone)]
+pub enum Construction {
+    /// The order `0..n`.
+    Identity,
+    /// A random shuffle of `0..n`.
+    Random { rng: Box<SmallRng> },
+    /// TSP tour that always visits the nearest unvisited city, starting at city 0.
+    NearestNeighbor,
+    /// TSP tour made by adding the shortest edges that keep it a valid tour.
+    GreedyEdge,
+    /// TSP tour growing by the city that is the cheapest to insert.
+    CheapestInsertion,
+    /// TSP tour growing by the city farthest from the tour, inserted where it is cheapest.
+    FarthestInsertion,
+    /// Bin packing order placing the heaviest items first in the first bin they fit in.
+    FirstFitDecreasing,
+    /// QAP assignment of the facilities with the highest flows to the most central locations.
+    GreedyQap,
+}
+impl Construction {
+    pub fn identity() -> Construction {
+        Construction::Identity
+    }
+    pub fn random(seed: Option<u64>) -> Construction {
+        let rng = match seed {
+            Some(seed) => SmallRng::seed_from_u64(seed),
+            None => SmallRng::from_entropy(),
+        };
+        Construction::Random { rng: Box::new(rng) }
+    }
+    pub fn nearest_neighbor() -> Construction {
+        Construction::NearestNeighbor
+    }
+    pub fn greedy_edge() -> Construction {
+        Construction::GreedyEdge
+    }
+    pub fn cheapest_insertion() -> Construction {
+        Construction::CheapestInsertion
+    }
+    pub fn farthest_insertion() -> Construction {
+        Construction::FarthestInsertion
+    }
+    pub fn first_fit_decreasing() -> Construction {
+        Construction::FirstFitDecreasing
+    }
+    pub fn greedy_qap() -> Construction {
+        Construction::GreedyQap
+    }
+
+    /// Whether this heuristic can build an order for the given evaluation.
+    pub(crate) fn supports(&self, evaluation: &Evaluation) -> bool {
+        match self {
+            Construction::Identity | Construction::Random { .. } => true,
+            Construction::NearestNeighbor
+            | Construction::GreedyEdge
+            | Construction::CheapestInsertion
+            | Construction::FarthestInsertion => matches!(evaluation, Evaluation::Tsp { .. }),
+            Construction::FirstFitDecreasing => matches!(
+                evaluation,
+                Evaluation::Bins { .. }
+                    | Evaluation::EmptySpace { .. }
+                    | Evaluation::EmptySpaceExp { .. }
+            ),
+            Construction::GreedyQap => matches!(evaluation, Evaluation::QAP { .. }),
+        }
+    }
+
+    /// Builds an order of `0..evaluation.length()`.
+    pub(crate) fn build(&mut self, evaluation: &Evaluation) -> Vec<usize> {
+        let len = evaluation.length();
+        match (self, evaluation) {
+            (Construction::Identity, _) => (0..len).collect(),
+            (Construction::Random { rng }, _) => {
+                let mut order: Vec<usize> = (0..len).collect();
+                order.shuffle(rng.as_mut());
+                order
+            }
+            (
+                Construction::NearestNeighbor,
+                Evaluation::Tsp {
+                    distance_matrix, ..
+                },
+            ) => nearest_neighbor(distance_matrix),
+            (
+                Construction::GreedyEdge,
+                Evaluation::Tsp {
+                    distance_matrix, ..
+                },
+            ) => greedy_edge(distance_matrix),
+            (
+                Construction::CheapestInsertion,
+                Evaluation::Tsp {
+                    distance_matrix, ..
+                },
+            ) => cheapest_insertion(distance_matrix),
+            (
+                Construction::FarthestInsertion,
+                Evaluation::Tsp {
+                    distance_matrix, ..
+                },
+            ) => farthest_insertion(distance_matrix),
+            (
+                Construction::FirstFitDecreasing,
+                Evaluation::Bins { weights, max_fill }
+                | Evaluation::EmptySpace { weights, max_fill }
+                | Evaluation::EmptySpaceExp { weights, max_fill },
+            ) => first_fit_decreasing(weights, *max_fill),
+            (
+                Construction::GreedyQap,
+                Evaluation::QAP {
+                    distance_matrix,
+                    flow_matrix,
+                    ..
+                },
+            ) => greedy_qap(distance_matrix, flow_matrix),
+            _ => panic!("Construction doesn't support this evaluation"),
+        }
+    }
+
+    pub(crate) fn set_seed(&mut self, seed: u64) {
+        if let Construction::Random { rng } = self {
+            **rng = SmallRng::seed_from_u64(seed);
+        }
+    }
+}
+
+fn nearest_neighbor(distance_matrix: &[Vec<f64>]) -> Vec<usize> {
+    let len = distance_matrix.len();
+    let mut visited = vec![false; len];
+    let mut tour = Vec::with_capacity(len);
+    let mut current = 0;
+    while tour.len() < len {
+        visited[current] = true;
+        tour.push(current);
+        let next = (0..len)
+            .filter(|j| !visited[*j])
+            .min_by(|a, b| distance_matrix[current][*a].total_cmp(&distance_matrix[current][*b]));
+        if let Some(next) = next {
+            current = next;
+        }
+    }
+    tour
+}
+
+fn greedy_edge(distance_matrix: &[Vec<f64>]) -> Vec<usize> {
+    let len = distance_matrix.len();
+    if len < 3 {
+        return (0..len).collect();
+    }
+    let cost = |i: usize, j: usize| distance_matrix[i][j] + distance_matrix[j][i];
+    let mut edges: Vec<(usize, usize)> = (0..len)
+        .flat_map(|i| ((i + 1)..len).map(move |j| (i, j)))
+        .collect();
+    edges.sort_by(|a, b| cost(a.0, a.1).total_cmp(&cost(b.0, b.1)));
+
+    // Union-find over the path fragments, so no edge closes a cycle too early.
+    let mut parent: Vec<usize> = (0..len).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    let mut neighbors: Vec<Vec<usize>> = vec![vec![]; len];
+    let mut added = 0;
+    for (i, j) in edges {
+        if added == len - 1 {
+            break;
+        }
+        if neighbors[i].len() == 2 || neighbors[j].len() == 2 {
+            continue;
+        }
+        let (root_i, root_j) = (root(&mut parent, i), root(&mut parent, j));
+        if root_i == root_j {
+            continue;
+        }
+        parent[root_i] = root_j;
+        neighbors[i].push(j);
+        neighbors[j].push(i);
+        added += 1;
+    }
+
+    // The fragments form a single path now, walk it from one of its ends.
+    let start = (0..len).find(|i| neighbors[*i].len() < 2).unwrap();
+    let mut tour = vec![start];
+    let mut previous = start;
+    let mut current = neighbors[start][0];
+    while current != start && tour.len() < len {
+        tour.push(current);
+        let next = neighbors[current].iter().find(|n| **n != previous);
+        match next {
+            Some(next) => {
+                previous = current;
+                current = *next;
+            }
+            None => break,
+        }
+    }
+    start_at_zero(tour)
+}
+
+/// Cost of inserting city `k` between `i` and `j`.
+fn insertion_cost(distance_matrix: &[Vec<f64>], i: usize, k: usize, j: usize) -> f64 {
+    distance_matrix[i][k] + distance_matrix[k][j] - distance_matrix[i][j]
+}
+
+/// Cheapest city of the tour to insert `k` after, the tour is given by its successors.
+fn cheapest_position(
+    distance_matrix: &[Vec<f64>],
+    next: &[usize],
+    tour: &[usize],
+    k: usize,
+) -> (usize, f64) {
+    tour.iter()
+        .map(|&i| (i, insertion_cost(distance_matrix, i, k, next[i])))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap()
+}
+
+fn cheapest_insertion(distance_matrix: &[Vec<f64>]) -> Vec<usize> {
+    let len = distance_matrix.len();
+    if len == 0 {
+        return vec![];
+    }
+    let mut next: Vec<usize> = (0..len).collect();
+    let mut tour = vec![0];
+    let mut in_tour = vec![false; len];
+    in_tour[0] = true;
+    // Best city to insert every city after and the cost of doing so.
+    let mut best: Vec<(usize, f64)> = (0..len)
+        .map(|k| (0, insertion_cost(distance_matrix, 0, k, 0)))
+        .collect();
+    while tour.len() < len {
+        let k = (0..len)
+            .filter(|k| !in_tour[*k])
+            .min_by(|a, b| best[*a].1.total_cmp(&best[*b].1))
+            .unwrap();
+        let i = best[k].0;
+        let j = next[i];
+        next[i] = k;
+        next[k] = j;
+        in_tour[k] = true;
+        tour.push(k);
+        for u in (0..len).filter(|u| !in_tour[*u]) {
+            if best[u].0 == i {
+                best[u] = cheapest_position(distance_matrix, &next, &tour, u);
+                continue;
+            }
+            for from in [i, k] {
+                let cost = insertion_cost(distance_matrix, from, u, next[from]);
+                if cost < best[u].1 {
+                    best[u] = (from, cost);
+                }
+            }
+        }
+    }
+    walk(&next)
+}
+
+fn farthest_insertion(distance_matrix: &[Vec<f64>]) -> Vec<usize> {
+    let len = distance_matrix.len();
+    if len == 0 {
+        return vec![];
+    }
+    let mut next: Vec<usize> = (0..len).collect();
+    let mut tour = vec![0];
+    let mut in_tour = vec![false; len];
+    in_tour[0] = true;
+    // Distance from every city to the closest city of the tour.
+    let mut to_tour: Vec<f64> = (0..len).map(|k| distance_matrix[0][k]).collect();
+    while tour.len() < len {
+        let k = (0..len)
+            .filter(|k| !in_tour[*k])
+            .max_by(|a, b| to_tour[*a].total_cmp(&to_tour[*b]))
+            .unwrap();
+        let (i, _) = cheapest_position(distance_matrix, &next, &tour, k);
+        let j = next[i];
+        next[i] = k;
+        next[k] = j;
+        in_tour[k] = true;
+        tour.push(k);
+        for u in 0..len {
+            to_tour[u] = to_tour[u].min(distance_matrix[k][u]);
+        }
+    }
+    walk(&next)
+}
+
+/// Tour starting at city 0 from the successor of every city.
+fn walk(next: &[usize]) -> Vec<usize> {
+    let mut tour = vec![0];
+    let mut current = next[0];
+    while current != 0 {
+        tour.push(current);
+        current = next[current];
+    }
+    tour
+}
+
+/// Rotates the tour so it starts at city 0.
+fn start_at_zero(mut tour: Vec<usize>) -> Vec<usize> {
+    if let Some(index) = tour.iter().position(|x| *x == 0) {
+        tour.rotate_left(index);
+    }
+    tour
+}
+
+fn first_fit_decreasing(weights: &[f64], max_fill: f64) -> Vec<usize> {
+    let mut items: Vec<usize> = (0..weights.len()).collect();
+    items.sort_by(|a, b| weights[*b].total_cmp(&weights[*a]));
+    let mut bins: Vec<(f64, Vec<usize>)> = vec![];
+    for item in items {
+        match bins
+            .iter_mut()
+            .find(|(fill, _)| fill + weights[item] <= max_fill)
+        {
+            Some((fill, content)) => {
+                *fill += weights[item];
+                content.push(item);
+            }
+            None => bins.push((weights[item], vec![item])),
+        }
+    }
+    bins.into_iter().flat_map(|(_, content)| content).collect()
+}
+
+fn greedy_qap(distance_matrix: &[Vec<f64>], flow_matrix: &[Vec<f64>]) -> Vec<usize> {
+    let len = distance_matrix.len();
+    let total = |matrix: &[Vec<f64>], i: usize| -> f64 {
+        (0..len).map(|j| matrix[i][j] + matrix[j][i]).sum()
+    };
+    let mut locations: Vec<usize> = (0..len).collect();
+    locations.sort_by(|a, b| total(distance_matrix, *a).total_cmp(&total(distance_matrix, *b)));
+    let mut facilities: Vec<usize> = (0..len).collect();
+    facilities.sort_by(|a, b| total(flow_matrix, *b).total_cmp(&total(flow_matrix, *a)));
+    let mut order = vec![0; len];
+    for (location, facility) in locations.into_iter().zip(facilities) {
+        order[location] = facility;
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Construction;
+    use crate::problem::Evaluation;
+
+    fn tsp() -> Evaluation {
+        Evaluation::tsp(vec![
+            vec![0.0, 2.0, 9.0, 10.0, 7.0],
+            vec![2.0, 0.0, 6.0, 4.0, 3.0],
+            vec![9.0, 6.0, 0.0, 8.0, 5.0],
+            vec![10.0, 4.0, 8.0, 0.0, 6.0],
+            vec![7.0, 3.0, 5.0, 6.0, 0.0],
+        ])
+    }
+
+    fn is_permutation(order: &[usize], len: usize) -> bool {
+        let mut sorted = order.to_vec();
+        sorted.sort();
+        sorted == (0..len).collect::<Vec<usize>>()
+    }
+
+    #[test]
+    fn tsp_construction_test() {
+        let eval = tsp();
+        let identity = eval.eval(&[0, 1, 2, 3, 4]);
+        for mut construction in [
+            Construction::nearest_neighbor(),
+            Construction::greedy_edge(),
+            Construction::cheapest_insertion(),
+            Construction::farthest_insertion(),
+        ] {
+            assert!(construction.supports(&eval));
+            let order = construction.build(&eval);
+            assert!(is_permutation(&order, 5));
+            assert_eq!(order[0], 0);
+            assert!(eval.eval(&order) < identity);
+        }
+        assert_eq!(
+            Construction::nearest_neighbor().build(&eval),
+            [0, 1, 4, 2, 3]
+        );
+        assert!(!Construction::greedy_qap().supports(&eval));
+    }
+
+    #[test]
+    fn random_construction_test() {
+        let eval = tsp();
+        let mut construction = Construction::random(Some(0));
+        let first = construction.build(&eval);
+        assert!(is_permutation(&first, 5));
+        construction.set_seed(0);
+        assert_eq!(construction.build(&eval), first);
+    }
+
+    #[test]
+    fn first_fit_decreasing_test() {
+        let eval = Evaluation::bins(vec![2.0, 5.0, 4.0, 7.0, 1.0, 3.0, 8.0], 10.0);
+        let order = Construction::first_fit_decreasing().build(&eval);
+        assert_eq!(order, [6, 0, 3, 5, 1, 2, 4]);
+        assert_eq!(eval.eval(&order), 2.0);
+    }
+
+    #[test]
+    fn greedy_qap_test() {
+        let eval = Evaluation::qap(
+            vec![
+                vec![0.0, 2.0, 9.0, 5.0],
+                vec![2.0, 0.0, 4.0, 6.0],
+                vec![9.0, 4.0, 0.0, 3.0],
+                vec![5.0, 6.0, 3.0, 0.0],
+            ],
+            vec![
+                vec![0.0, 2.0, 0.0, 0.0],
+                vec![2.0, 0.0, 4.0, 0.0],
+                vec![0.0, 4.0, 0.0, 8.0],
+                vec![0.0, 0.0, 8.0, 0.0],
+            ],
+        );
+        let order = Construction::greedy_qap().build(&eval);
+        assert_eq!(order, [1, 2, 0, 3]);
+    }
+}
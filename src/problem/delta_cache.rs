@@ -0,0 +1,352 @@
+use super::evaluation::RemovalTimes;
+use super::{Evaluation, Move};
+
+/// Values kept for every position of the state, of which only the first `valid` are up to
+/// date. Undoing the move done last gives back the values from before it, so probing a move
+/// costs nothing.
+pub(crate) struct PrefixCache<T> {
+    values: Vec<T>,
+    valid: usize,
+    /// Move done last with the count of valid values before it, until the values change.
+    done: Option<(Move, usize)>,
+}
+impl<T> Default for PrefixCache<T> {
+    fn default() -> Self {
+        PrefixCache {
+            values: vec![],
+            valid: 0,
+            done: None,
+        }
+    }
+}
+impl<T> PrefixCache<T> {
+    /// Keeps the first `kept` values when `mov` is done.
+    fn do_mov(&mut self, mov: &Move, kept: usize) {
+        self.done = Some((mov.clone(), self.valid));
+        self.valid = self.valid.min(kept);
+    }
+
+    /// Keeps the first `kept` values when `mov` is undone, or all values from before it if
+    /// it is the move done last.
+    fn undo_mov(&mut self, mov: &Move, kept: usize) {
+        self.valid = match self.done.take() {
+            Some((done, valid)) if done == *mov => valid,
+            _ => self.valid.min(kept),
+        };
+    }
+
+    fn clear(&mut self) {
+        self.valid = 0;
+        self.done = None;
+    }
+
+    /// All `len` values, `update` brings them up to date from the first outdated position.
+    fn get(&mut self, len: usize, update: impl FnOnce(&mut Vec<T>, usize)) -> &[T] {
+        if self.valid < len {
+            update(&mut self.values, self.valid);
+            self.valid = len;
+            self.done = None;
+        }
+        &self.values
+    }
+}
+
+/// What an [ArrayProblem](super::ArrayProblem) keeps about its state to evaluate the moves of
+/// its evaluation faster. Every move goes through [update](DeltaCache::update) before it is
+/// done or undone, any other change of the state through [invalidate](DeltaCache::invalidate).
+pub(crate) enum DeltaCache {
+    /// The evaluation computes every delta from the state alone.
+    None,
+    /// Score and level before every position.
+    PrefixLevels(PrefixCache<(f64, f64)>),
+    /// Forward and backward path length up to every position of an asymmetric TSP.
+    PathLengths(PrefixCache<(f64, f64)>),
+    /// Delta of every swap of a QAP, only kept during exhaustive searches and empty when
+    /// outdated.
+    SwapDeltas {
+        deltas: Vec<Vec<f64>>,
+        /// Swap done since the deltas were last brought up to date. A probe that is undone
+        /// right away costs nothing, the deltas only follow the swaps that stay.
+        pending: Option<(usize, usize)>,
+    },
+    /// Flow shop times with the segment of the last evaluated relocation taken out, `None`
+    /// when outdated.
+    RemovalTimes(Option<RemovalTimes>),
+    /// Index of every node of a linear arrangement, empty when outdated.
+    Positions(Vec<usize>),
+}
+impl DeltaCache {
+    /// The cache of the evaluation, empty until the first delta.
+    pub(crate) fn new(evaluation: &Evaluation) -> Self {
+        if evaluation.has_prefix_levels() {
+            DeltaCache::PrefixLevels(PrefixCache::default())
+        } else if evaluation.is_asymmetric_tsp() {
+            DeltaCache::PathLengths(PrefixCache::default())
+        } else if matches!(evaluation, Evaluation::QAP { .. }) {
+            DeltaCache::SwapDeltas {
+                deltas: vec![],
+                pending: None,
+            }
+        } else if evaluation.is_flow_shop() {
+            DeltaCache::RemovalTimes(None)
+        } else if evaluation.is_linear_arrangement() {
+            DeltaCache::Positions(vec![])
+        } else {
+            DeltaCache::None
+        }
+    }
+
+    /// Keeps what is known about `order` for a move about to be done, or undone if `undo`.
+    pub(crate) fn update(
+        &mut self,
+        evaluation: &Evaluation,
+        order: &[usize],
+        mov: &Move,
+        undo: bool,
+    ) {
+        // The path lengths before the first changed position stay the same, the levels up to
+        // the one before it too.
+        let start = mov.changed_range().map(|(start, _)| start);
+        match self {
+            DeltaCache::None => {}
+            DeltaCache::PrefixLevels(levels) => {
+                let kept = start.map_or(0, |start| start + 1);
+                if undo {
+                    levels.undo_mov(mov, kept);
+                } else {
+                    levels.do_mov(mov, kept);
+                }
+            }
+            DeltaCache::PathLengths(path_lengths) => {
+                let kept = start.unwrap_or(0);
+                if undo {
+                    path_lengths.undo_mov(mov, kept);
+                } else {
+                    path_lengths.do_mov(mov, kept);
+                }
+            }
+            // Undoing the pending swap brings back the state the deltas belong to, any other
+            // move than a swap makes them outdated.
+            DeltaCache::SwapDeltas { deltas, pending } => match mov {
+                Move::Swap(i, j) if !deltas.is_empty() => {
+                    let swap = (*i.min(j), *i.max(j));
+                    if undo && *pending == Some(swap) {
+                        *pending = None;
+                    } else {
+                        apply_pending_swap(evaluation, order, deltas, pending);
+                        *pending = Some(swap);
+                    }
+                }
+                _ => {
+                    deltas.clear();
+                    *pending = None;
+                }
+            },
+            DeltaCache::RemovalTimes(removal) => *removal = None,
+            DeltaCache::Positions(positions) => positions.clear(),
+        }
+    }
+
+    /// Forgets everything known about the state.
+    pub(crate) fn invalidate(&mut self) {
+        match self {
+            DeltaCache::None => {}
+            DeltaCache::PrefixLevels(values) | DeltaCache::PathLengths(values) => values.clear(),
+            DeltaCache::SwapDeltas { deltas, pending } => {
+                deltas.clear();
+                *pending = None;
+            }
+            DeltaCache::RemovalTimes(removal) => *removal = None,
+            DeltaCache::Positions(positions) => positions.clear(),
+        }
+    }
+
+    /// Computes the QAP swap deltas before a neighborhood with swaps is searched, so every
+    /// following swap is evaluated in O(1) time. Sampling searches never build them.
+    pub(crate) fn prepare_swaps(&mut self, evaluation: &Evaluation, order: &[usize]) {
+        if let DeltaCache::SwapDeltas { deltas, pending } = self {
+            if deltas.is_empty() {
+                evaluation.update_swap_deltas(order, deltas, None);
+            }
+            apply_pending_swap(evaluation, order, deltas, pending);
+        }
+    }
+
+    /// Delta of a move on `order`, filling the cache on the way.
+    pub(crate) fn delta(
+        &mut self,
+        evaluation: &Evaluation,
+        mov: &Move,
+        order: &mut [usize],
+    ) -> f64 {
+        match (self, mov) {
+            // Relocations are enumerated per segment, which all share the same removal times.
+            (DeltaCache::RemovalTimes(removal), Move::Relocate(from, to, len)) => {
+                let times = match removal.take() {
+                    Some(times) if times.is_of(*from, *len) => times,
+                    _ => evaluation.removal_times(order, *from, *len),
+                };
+                let delta = evaluation.delta_insertion(order, &times, *to);
+                *removal = Some(times);
+                delta
+            }
+            (DeltaCache::SwapDeltas { deltas, pending }, Move::Swap(i, j))
+                if !deltas.is_empty() =>
+            {
+                apply_pending_swap(evaluation, order, deltas, pending);
+                deltas[*i.min(j)][*i.max(j)]
+            }
+            (DeltaCache::PrefixLevels(levels), _) => {
+                let levels = levels.get(order.len() + 1, |levels, start| {
+                    evaluation.update_prefix_levels(order, levels, start)
+                });
+                evaluation.delta_prefix(mov, order, levels)
+            }
+            (DeltaCache::PathLengths(path_lengths), _) => {
+                let path_lengths = path_lengths.get(order.len(), |path_lengths, start| {
+                    evaluation.update_path_lengths(order, path_lengths, start)
+                });
+                evaluation.delta_eval_with(mov, order, Some(path_lengths))
+            }
+            (DeltaCache::Positions(positions), _) => {
+                if positions.is_empty() {
+                    *positions = super::r#move::positions(order);
+                }
+                evaluation.delta_arrangement(mov, order, positions)
+            }
+            _ => evaluation.delta_eval(mov, order),
+        }
+    }
+}
+
+/// Brings the QAP swap deltas up to date with the pending swap.
+fn apply_pending_swap(
+    evaluation: &Evaluation,
+    order: &[usize],
+    deltas: &mut Vec<Vec<f64>>,
+    pending: &mut Option<(usize, usize)>,
+) {
+    if let Some(swap) = pending.take() {
+        evaluation.update_swap_deltas(order, deltas, Some(swap));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DeltaCache;
+    use crate::problem::{Evaluation, Move, MoveType};
+
+    /// Count of up to date prefix values of the cache.
+    fn valid(cache: &DeltaCache) -> usize {
+        match cache {
+            DeltaCache::PrefixLevels(values) | DeltaCache::PathLengths(values) => values.valid,
+            _ => panic!("Only prefix caches have valid values"),
+        }
+    }
+
+    fn do_mov(cache: &mut DeltaCache, eval: &Evaluation, order: &mut [usize], mov: &Move) {
+        cache.update(eval, order, mov, false);
+        mov.do_move(order);
+    }
+
+    fn undo_mov(cache: &mut DeltaCache, eval: &Evaluation, order: &mut [usize], mov: &Move) {
+        cache.update(eval, order, mov, true);
+        mov.undo_move(order);
+    }
+
+    /// Checks the delta of every move against a full evaluation.
+    fn check_deltas(
+        cache: &mut DeltaCache,
+        eval: &Evaluation,
+        order: &mut [usize],
+        moves: &[Move],
+    ) {
+        for mov in moves {
+            let score = eval.eval(order);
+            let delta = cache.delta(eval, mov, order);
+            do_mov(cache, eval, order, mov);
+            assert!((eval.eval(order) - score - delta).abs() < 1e-9);
+            undo_mov(cache, eval, order, mov);
+        }
+    }
+
+    #[test]
+    fn probe_prefix_levels_test() {
+        let eval = Evaluation::bins(vec![4.0, 3.0, 5.0, 2.0, 6.0, 1.0, 3.0, 4.0], 8.0);
+        let mut reverse = MoveType::reverse(Some(0));
+        reverse.set_size(8);
+        let moves = reverse.get_all_mov();
+        let mut order: Vec<usize> = (0..8).collect();
+        let mut cache = DeltaCache::new(&eval);
+        cache.delta(&eval, &moves[0], &mut order);
+        assert_eq!(valid(&cache), 9);
+
+        // Undoing a probe keeps every level, the levels after a move that stays are
+        // computed again from its first position.
+        for mov in &moves {
+            do_mov(&mut cache, &eval, &mut order, mov);
+            undo_mov(&mut cache, &eval, &mut order, mov);
+            assert_eq!(valid(&cache), 9);
+        }
+        do_mov(&mut cache, &eval, &mut order, &Move::Reverse(3, 6));
+        assert_eq!(valid(&cache), 4);
+        check_deltas(&mut cache, &eval, &mut order, &moves);
+    }
+
+    #[test]
+    fn probe_path_lengths_test() {
+        let distance_matrix: Vec<Vec<f64>> = (0..7)
+            .map(|i| (0..7).map(|j| ((i * 5 + j * 3) % 11) as f64).collect())
+            .collect();
+        let eval = Evaluation::tsp(distance_matrix);
+        assert!(eval.is_asymmetric_tsp());
+        let mut reverse = MoveType::reverse(Some(0));
+        reverse.set_size(7);
+        let moves = reverse.get_all_mov();
+        let mut order: Vec<usize> = (0..7).collect();
+        let mut cache = DeltaCache::new(&eval);
+        cache.delta(&eval, &moves[0], &mut order);
+        assert_eq!(valid(&cache), 7);
+
+        for mov in &moves {
+            do_mov(&mut cache, &eval, &mut order, mov);
+            undo_mov(&mut cache, &eval, &mut order, mov);
+            assert_eq!(valid(&cache), 7);
+        }
+        do_mov(&mut cache, &eval, &mut order, &Move::Reverse(2, 5));
+        assert_eq!(valid(&cache), 2);
+        check_deltas(&mut cache, &eval, &mut order, &moves);
+    }
+
+    #[test]
+    fn invalidate_test() {
+        let eval = Evaluation::qap(
+            vec![
+                vec![0.0, 1.0, 2.0],
+                vec![1.0, 0.0, 3.0],
+                vec![2.0, 3.0, 0.0],
+            ],
+            vec![
+                vec![0.0, 5.0, 1.0],
+                vec![5.0, 0.0, 4.0],
+                vec![1.0, 4.0, 0.0],
+            ],
+        );
+        let mut swap = MoveType::swap(Some(0));
+        swap.set_size(3);
+        let moves = swap.get_all_mov();
+        let mut order: Vec<usize> = (0..3).collect();
+        let mut cache = DeltaCache::new(&eval);
+        cache.prepare_swaps(&eval, &order);
+        check_deltas(&mut cache, &eval, &mut order, &moves);
+
+        // A state set from outside leaves the deltas of the old one behind.
+        order = vec![2, 0, 1];
+        cache.invalidate();
+        let DeltaCache::SwapDeltas { deltas, .. } = &cache else {
+            panic!("QAP evaluations keep swap deltas")
+        };
+        assert!(deltas.is_empty());
+        check_deltas(&mut cache, &eval, &mut order, &moves);
+    }
+}
@@ -1,5 +1,13 @@
+use super::r#move::positions;
+use super::{Move, MoveType, Reconnection};
 use crate::aidfunc::check_if_distance_matrix_symmetric;
-use super::MoveType;
+use std::sync::Arc;
+
+/// Function giving the score of a state.
+pub type EvalFn = Arc<dyn Fn(&[usize]) -> f64 + Send + Sync>;
+/// Function giving the change in score if a move would be performed on a state.
+pub type DeltaFn = Arc<dyn Fn(&[usize], &Move) -> f64 + Send + Sync>;
+
 #[derive(Clone)]
 pub enum Evaluation {
     Bins {
@@ -18,10 +26,124 @@ pub enum Evaluation {
         distance_matrix: Vec<Vec<f64>>,
         symmetric: bool,
     },
+    TspPath {
+        distance_matrix: Vec<Vec<f64>>,
+        symmetric: bool,
+        start: Option<usize>,
+        end: Option<usize>,
+    },
     QAP {
         distance_matrix: Vec<Vec<f64>>,
         flow_matrix: Vec<Vec<f64>>,
+        symmetric: bool,
+    },
+    SingleMachine {
+        processing_times: Vec<f64>,
+        due_dates: Vec<f64>,
+        weights: Vec<f64>,
+        setup_times: Option<Vec<Vec<f64>>>,
+    },
+    TspTw {
+        distance_matrix: Vec<Vec<f64>>,
+        time_windows: Vec<(f64, f64)>,
+        service_times: Vec<f64>,
+        penalty: f64,
+    },
+    Cvrp {
+        distance_matrix: Vec<Vec<f64>>,
+        demands: Vec<f64>,
+        capacity: f64,
+        vehicles: Option<usize>,
+        penalty: f64,
+    },
+    FlowShop {
+        processing_times: Vec<Vec<f64>>,
+        objective: FlowShopObjective,
+        due_dates: Vec<f64>,
+        weights: Vec<f64>,
     },
+    LinearOrdering {
+        weight_matrix: Vec<Vec<f64>>,
+    },
+    LinearArrangement {
+        /// Neighbors of every node with the weight of the edge between them.
+        neighbors: Vec<Vec<(usize, f64)>>,
+        objective: ArrangementObjective,
+    },
+    Custom {
+        size: usize,
+        eval: EvalFn,
+        delta: Option<DeltaFn>,
+    },
+}
+
+/// Visit of a node on a tour with time windows.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Visit {
+    pub node: usize,
+    pub arrival: f64,
+    /// When the service starts, after waiting for the time window to open.
+    pub start: f64,
+    /// Time the arrival is after the time window closes.
+    pub lateness: f64,
+}
+
+/// Feasibility of a tour with time windows.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeWindowReport {
+    pub travel: f64,
+    pub lateness: f64,
+    /// Every visit in order from the depot, the return to the depot last.
+    pub visits: Vec<Visit>,
+}
+impl TimeWindowReport {
+    /// Whether every node is reached before its time window closes.
+    pub fn is_feasible(&self) -> bool {
+        self.lateness == 0.0
+    }
+}
+
+/// What a flow shop schedule is scored on, to be minimized.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FlowShopObjective {
+    /// Completion time of the last job on the last machine.
+    Makespan,
+    /// Weighted sum of the completion times of all jobs.
+    TotalFlowTime,
+    /// Weighted sum of the time every job completes after its due date.
+    TotalTardiness,
+}
+
+/// What a linear arrangement of a graph is scored on, to be minimized.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArrangementObjective {
+    /// Weighted sum of the lengths of all edges, the minimum linear arrangement.
+    Sum,
+    /// Weighted length of the longest edge.
+    Bandwidth,
+}
+
+/// Completion times of a flow shop order with the segment of `len` jobs at `from` taken out,
+/// with which every insertion of that segment is evaluated in O(len * machines) time for the
+/// makespan (Taillard's acceleration).
+pub(crate) struct RemovalTimes {
+    from: usize,
+    len: usize,
+    /// Completion time of every remaining job on every machine.
+    heads: Vec<Vec<f64>>,
+    /// Time from the start of every remaining job on every machine until the end of the
+    /// schedule, followed by a row of zeros.
+    tails: Vec<Vec<f64>>,
+    /// Summed cost of the first `i` remaining jobs.
+    costs: Vec<f64>,
+    /// Score of the order the segment is taken from.
+    score: f64,
+}
+impl RemovalTimes {
+    /// Whether these are the times with the segment of `len` jobs at `from` taken out.
+    pub(crate) fn is_of(&self, from: usize, len: usize) -> bool {
+        self.from == from && self.len == len
+    }
 }
 impl Evaluation {
     pub fn bins(weights: Vec<f64>, max_fill: f64) -> Evaluation {
@@ -34,144 +156,1082 @@ impl Evaluation {
         Evaluation::EmptySpaceExp { weights, max_fill }
     }
     pub fn tsp(distance_matrix: Vec<Vec<f64>>) -> Evaluation {
-        let symmetric=check_if_distance_matrix_symmetric(&distance_matrix);
+        let symmetric = check_if_distance_matrix_symmetric(&distance_matrix);
         Evaluation::Tsp {
             distance_matrix,
             symmetric,
         }
     }
+    /// Hamiltonian path to be minimized, a TSP tour without the edge from the last node back
+    /// to the first. The path starts at `start` and ends at `end` if given, those nodes stay
+    /// on the first and last position when the move type has
+    /// [fixed ends](super::MoveType::fixed_ends).
+    pub fn tsp_path(
+        distance_matrix: Vec<Vec<f64>>,
+        start: Option<usize>,
+        end: Option<usize>,
+    ) -> Evaluation {
+        let nodes = distance_matrix.len();
+        assert!(
+            start.iter().chain(&end).all(|node| *node < nodes),
+            "The fixed ends must be nodes of the distance matrix"
+        );
+        assert!(
+            start.is_none() || start != end || nodes == 1,
+            "The path can't start and end at the same node"
+        );
+        let symmetric = check_if_distance_matrix_symmetric(&distance_matrix);
+        Evaluation::TspPath {
+            distance_matrix,
+            symmetric,
+            start,
+            end,
+        }
+    }
+    /// Quadratic assignment on a permutation giving the facility of every location. If both
+    /// matrices are symmetric every pair of locations is counted once, otherwise the score is
+    /// the full sum over all ordered pairs, the diagonal included.
     pub fn qap(distance_matrix: Vec<Vec<f64>>, flow_matrix: Vec<Vec<f64>>) -> Evaluation {
+        let symmetric = check_if_distance_matrix_symmetric(&distance_matrix)
+            && check_if_distance_matrix_symmetric(&flow_matrix);
         Evaluation::QAP {
             distance_matrix,
             flow_matrix,
+            symmetric,
+        }
+    }
+
+    /// TSP with time windows to be minimized: the length of the tour plus `penalty` times the
+    /// summed lateness. The tour leaves node 0, the depot, when its time window opens and
+    /// returns there, a vehicle arriving before a time window opens waits, and every node
+    /// takes its service time. The penalty is the sum of all distances by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `time_windows`: earliest and latest arrival at every node.
+    /// * `service_times`: time spent at every node, 0 by default.
+    pub fn tsp_tw(
+        distance_matrix: Vec<Vec<f64>>,
+        time_windows: Vec<(f64, f64)>,
+        service_times: Option<Vec<f64>>,
+        penalty: Option<f64>,
+    ) -> Evaluation {
+        let nodes = distance_matrix.len();
+        let service_times = service_times.unwrap_or(vec![0.0; nodes]);
+        assert_eq!(time_windows.len(), nodes, "Every node needs a time window");
+        assert_eq!(
+            service_times.len(),
+            nodes,
+            "Every node needs a service time"
+        );
+        let penalty = penalty.unwrap_or(distance_matrix.iter().flatten().sum());
+        Evaluation::TspTw {
+            distance_matrix,
+            time_windows,
+            service_times,
+            penalty,
+        }
+    }
+
+    /// Capacitated vehicle routing to be minimized on a giant tour of the customers: the total
+    /// distance of the routes plus `penalty` times the demand above the capacity of every
+    /// route. The penalty is the sum of all distances by default, which makes every solution
+    /// that is at least one unit over capacity worse than any feasible one.
+    ///
+    /// # Arguments
+    ///
+    /// * `distance_matrix`: distances between all nodes, node 0 is the depot and element `c`
+    ///   of the permutation is node `c + 1`.
+    /// * `demands`: demand of every node, the one of the depot is ignored.
+    /// * `vehicles`: if given, the permutation also holds `vehicles - 1` separators, the
+    ///   elements from the number of customers onward, that each end a route. Otherwise
+    ///   Prins' split cuts the giant tour into the routes with the lowest score.
+    pub fn cvrp(
+        distance_matrix: Vec<Vec<f64>>,
+        demands: Vec<f64>,
+        capacity: f64,
+        vehicles: Option<usize>,
+        penalty: Option<f64>,
+    ) -> Evaluation {
+        assert!(
+            !distance_matrix.is_empty(),
+            "The distance matrix needs a depot"
+        );
+        assert_eq!(
+            demands.len(),
+            distance_matrix.len(),
+            "Every node needs a demand"
+        );
+        assert!(vehicles != Some(0), "There has to be at least one vehicle");
+        let penalty = penalty.unwrap_or(distance_matrix.iter().flatten().sum());
+        Evaluation::Cvrp {
+            distance_matrix,
+            demands,
+            capacity,
+            vehicles,
+            penalty,
+        }
+    }
+
+    /// Single machine scheduling to be minimized: the total weighted tardiness of the jobs
+    /// done in order. With `setup_times[i][j]` the machine needs that time between job `i`
+    /// and job `j`, the first job needs no setup. Every weight is 1 by default.
+    pub fn single_machine(
+        processing_times: Vec<f64>,
+        due_dates: Vec<f64>,
+        weights: Option<Vec<f64>>,
+        setup_times: Option<Vec<Vec<f64>>>,
+    ) -> Evaluation {
+        let jobs = processing_times.len();
+        let weights = weights.unwrap_or(vec![1.0; jobs]);
+        assert_eq!(due_dates.len(), jobs, "Every job needs a due date");
+        assert_eq!(weights.len(), jobs, "Every job needs a weight");
+        if let Some(setup_times) = &setup_times {
+            assert!(
+                setup_times.len() == jobs && setup_times.iter().all(|row| row.len() == jobs),
+                "Setup times need a value for every pair of jobs"
+            );
+        }
+        Evaluation::SingleMachine {
+            processing_times,
+            due_dates,
+            weights,
+            setup_times,
+        }
+    }
+
+    /// Permutation flow shop to be minimized, every job goes over the machines in the same
+    /// order and every machine handles the jobs in the order of the permutation.
+    ///
+    /// # Arguments
+    ///
+    /// * `processing_times`: time every job takes on every machine, `[job][machine]`.
+    /// * `due_dates`: needed for the total tardiness.
+    /// * `weights`: weight of every job in the total flow time and tardiness, 1 by default.
+    pub fn flow_shop(
+        processing_times: Vec<Vec<f64>>,
+        objective: FlowShopObjective,
+        due_dates: Option<Vec<f64>>,
+        weights: Option<Vec<f64>>,
+    ) -> Evaluation {
+        let jobs = processing_times.len();
+        assert!(
+            processing_times
+                .iter()
+                .all(|times| !times.is_empty() && times.len() == processing_times[0].len()),
+            "Every job needs a processing time on every machine"
+        );
+        assert!(
+            due_dates.is_some() || objective != FlowShopObjective::TotalTardiness,
+            "Total tardiness needs due dates"
+        );
+        let due_dates = due_dates.unwrap_or(vec![0.0; jobs]);
+        let weights = weights.unwrap_or(vec![1.0; jobs]);
+        assert_eq!(due_dates.len(), jobs, "Every job needs a due date");
+        assert_eq!(weights.len(), jobs, "Every job needs a weight");
+        Evaluation::FlowShop {
+            processing_times,
+            objective,
+            due_dates,
+            weights,
+        }
+    }
+
+    /// Linear ordering to be maximized: the sum of the weights above the diagonal of the
+    /// weight matrix with its rows and columns in the order of the permutation, so
+    /// `weight_matrix[a][b]` counts if `a` comes before `b`.
+    pub fn linear_ordering(weight_matrix: Vec<Vec<f64>>) -> Evaluation {
+        Evaluation::LinearOrdering { weight_matrix }
+    }
+
+    /// Graph layout on a line to be minimized, the permutation gives the node on every
+    /// position and an edge is as long as the distance between the positions of its nodes.
+    ///
+    /// # Arguments
+    ///
+    /// * `nodes`: number of nodes of the graph.
+    /// * `edges`: every edge as the pair of nodes it connects.
+    /// * `weights`: weight every edge length is multiplied with, 1 by default.
+    pub fn linear_arrangement(
+        nodes: usize,
+        edges: Vec<(usize, usize)>,
+        objective: ArrangementObjective,
+        weights: Option<Vec<f64>>,
+    ) -> Evaluation {
+        let weights = weights.unwrap_or(vec![1.0; edges.len()]);
+        assert_eq!(weights.len(), edges.len(), "Every edge needs a weight");
+        let mut neighbors = vec![vec![]; nodes];
+        for ((u, v), weight) in edges.into_iter().zip(weights) {
+            assert!(
+                u < nodes && v < nodes,
+                "Edges must connect nodes of the graph"
+            );
+            neighbors[u].push((v, weight));
+            neighbors[v].push((u, weight));
+        }
+        Evaluation::LinearArrangement {
+            neighbors,
+            objective,
         }
     }
 
-    pub(crate) fn delta_eval(
+    /// Evaluation defined by user functions on a state of `size` positions, used to wrap
+    /// Python callables.
+    ///
+    /// # Arguments
+    ///
+    /// * `eval`: returns the score of a state.
+    /// * `delta`: returns the change in score if the move would be performed on the state. If
+    ///   not given, the state is evaluated before and after the move.
+    pub fn custom(size: usize, eval: EvalFn, delta: Option<DeltaFn>) -> Evaluation {
+        Evaluation::Custom { size, eval, delta }
+    }
+
+    pub(crate) fn delta_eval(&self, mov: &Move, order: &mut [usize]) -> f64 {
+        self.delta_eval_with(mov, order, None)
+    }
+
+    /// Delta evaluation, reversals of an asymmetric TSP take O(1) time if the
+    /// [path_lengths](Evaluation::update_path_lengths) of the order are given.
+    pub(crate) fn delta_eval_with(
         &self,
-        indices: (usize, usize),
-        move_type: &MoveType,
-        order: &mut Vec<usize>,
+        mov: &Move,
+        order: &mut [usize],
+        path_lengths: Option<&[(f64, f64)]>,
     ) -> f64 {
         match self {
             Evaluation::Bins { .. }
             | Evaluation::EmptySpace { .. }
-            | Evaluation::EmptySpaceExp { .. } => {
-                let first = self.eval(order);
-                move_type.do_move(order, indices);
-                let sec = self.eval(order);
-                move_type.do_move(order, indices);
-                sec - first
-            }
+            | Evaluation::EmptySpaceExp { .. }
+            | Evaluation::SingleMachine { .. } => self.delta_prefix_walk(mov, order),
             Evaluation::Tsp {
                 distance_matrix,
                 symmetric,
+            }
+            | Evaluation::TspPath {
+                distance_matrix,
+                symmetric,
+                ..
             } => {
                 let mut init_score = 0.0;
                 let mut next_score = 0.0;
-                if matches!(move_type, MoveType::Swap { rng: _, size: _ })
-                    || matches!(move_type, MoveType::Tsp { rng: _, size: _ })
-                {
-                    let from = indices.0;
-                    let to = indices.1;
-                    if from > 0 {
-                        init_score += distance_matrix[order[from - 1]][order[from]];
-                    } else {
-                        init_score += distance_matrix[order[order.len() - 1]][order[from]];
-                    }
-
-                    init_score += distance_matrix[order[from]][order[from + 1]];
-                    if from != to - 1 {
-                        init_score += distance_matrix[order[to - 1]][order[to]];
-                    }
-
-                    init_score += distance_matrix[order[to]][order[(to + 1) % order.len()]];
+                match *mov {
+                    Move::Swap(from, to) => {
+                        if from > 0 {
+                            init_score += distance_matrix[order[from - 1]][order[from]];
+                        } else if to != order.len() - 1 {
+                            init_score += distance_matrix[order[order.len() - 1]][order[from]];
+                        }
 
-                    move_type.do_move(order, indices);
+                        init_score += distance_matrix[order[from]][order[from + 1]];
+                        if from != to - 1 {
+                            init_score += distance_matrix[order[to - 1]][order[to]];
+                        }
 
-                    if from > 0 {
-                        next_score += distance_matrix[order[from - 1]][order[from]];
-                    } else {
-                        next_score += distance_matrix[order[order.len() - 1]][order[from]];
-                    }
+                        init_score += distance_matrix[order[to]][order[(to + 1) % order.len()]];
 
-                    next_score += distance_matrix[order[from]][order[from + 1]];
-                    if from != to - 1 {
-                        next_score += distance_matrix[order[to - 1]][order[to]];
-                    }
-                    next_score += distance_matrix[order[to]][order[(to + 1) % order.len()]];
+                        mov.do_move(order);
 
-                    move_type.do_move(order, indices);
-                } else {
-                    if *symmetric {
-                        if indices.0 > 0 {
-                            init_score += distance_matrix[order[indices.0 - 1]][order[indices.0]];
-                        } else {
-                            init_score += distance_matrix[order[order.len() - 1]][order[indices.0]];
+                        if from > 0 {
+                            next_score += distance_matrix[order[from - 1]][order[from]];
+                        } else if to != order.len() - 1 {
+                            next_score += distance_matrix[order[order.len() - 1]][order[from]];
                         }
-                        init_score +=
-                            distance_matrix[order[indices.1]][order[(indices.1 + 1) % order.len()]];
 
-                        move_type.do_move(order, indices);
-
-                        if indices.0 > 0 {
-                            next_score += distance_matrix[order[indices.0 - 1]][order[indices.0]];
-                        } else {
-                            next_score += distance_matrix[order[order.len() - 1]][order[indices.0]];
+                        next_score += distance_matrix[order[from]][order[from + 1]];
+                        if from != to - 1 {
+                            next_score += distance_matrix[order[to - 1]][order[to]];
                         }
-                        next_score +=
-                            distance_matrix[order[indices.1]][order[(indices.1 + 1) % order.len()]];
+                        next_score += distance_matrix[order[to]][order[(to + 1) % order.len()]];
 
-                        move_type.do_move(order, indices);
-                    } else {
-                        for i in indices.0..indices.1 {
-                            init_score += distance_matrix[order[i]][order[i + 1]];
-                        }
-                        if indices.0 > 0 {
-                            init_score += distance_matrix[order[indices.0] - 1][order[indices.0]];
+                        mov.undo_move(order);
+                    }
+                    Move::Reverse(from, to) => {
+                        let n = order.len();
+                        let d = distance_matrix;
+                        if from == 0 && to == n - 1 {
+                            // The whole tour is walked backwards.
+                            if !*symmetric {
+                                init_score += d[order[n - 1]][order[0]];
+                                next_score += reversal_delta(d, order, from, to, path_lengths)
+                                    + d[order[0]][order[n - 1]];
+                            }
                         } else {
-                            init_score += distance_matrix[order[order.len() - 1]][order[indices.0]];
+                            let (prev, next) = (order[(from + n - 1) % n], order[(to + 1) % n]);
+                            init_score += d[prev][order[from]] + d[order[to]][next];
+                            next_score += d[prev][order[to]] + d[order[from]][next];
+                            if !*symmetric {
+                                next_score += reversal_delta(d, order, from, to, path_lengths);
+                            }
                         }
-
-                        init_score +=
-                            distance_matrix[order[indices.1]][order[(indices.1 + 1) % order.len()]];
-                        move_type.do_move(order, indices);
-                        for i in indices.0..indices.1 {
-                            next_score += distance_matrix[order[i]][order[i + 1]];
+                    }
+                    Move::Relocate(from, to, len) => {
+                        let n = order.len();
+                        let d = distance_matrix;
+                        // Order with the segment taken out, the segment goes back in before
+                        // position `to` of it.
+                        let rest = |i: usize| {
+                            let i = i % (n - len);
+                            if i < from {
+                                order[i]
+                            } else {
+                                order[i + len]
+                            }
+                        };
+                        let (first, last) = (order[from], order[from + len - 1]);
+                        let (prev, next) = (order[(from + n - 1) % n], order[(from + len) % n]);
+                        let (before, after) = (rest(to + n - len - 1), rest(to));
+                        init_score += d[prev][first] + d[last][next] + d[before][after];
+                        next_score += d[prev][next] + d[before][first] + d[last][after];
+                    }
+                    Move::ThreeOpt(i, j, k, reconnection) => {
+                        let d = distance_matrix;
+                        let (prev, next) = (order[i - 1], order[(k + 1) % order.len()]);
+                        let (b_first, b_last) = (order[i], order[j]);
+                        let (c_first, c_last) = (order[j + 1], order[k]);
+                        init_score += d[prev][b_first] + d[b_last][c_first] + d[c_last][next];
+                        next_score += match reconnection {
+                            Reconnection::ReverseBoth => {
+                                d[prev][b_last] + d[b_first][c_last] + d[c_first][next]
+                            }
+                            Reconnection::Exchange => {
+                                d[prev][c_first] + d[c_last][b_first] + d[b_last][next]
+                            }
+                            Reconnection::ExchangeReverseSecond => {
+                                d[prev][c_last] + d[c_first][b_first] + d[b_last][next]
+                            }
+                            Reconnection::ExchangeReverseFirst => {
+                                d[prev][c_first] + d[c_last][b_last] + d[b_first][next]
+                            }
+                        };
+                        if !*symmetric {
+                            let (reverse_first, reverse_second) = match reconnection {
+                                Reconnection::ReverseBoth => (true, true),
+                                Reconnection::Exchange => (false, false),
+                                Reconnection::ExchangeReverseSecond => (false, true),
+                                Reconnection::ExchangeReverseFirst => (true, false),
+                            };
+                            if reverse_first {
+                                next_score += reversal_delta(d, order, i, j, path_lengths);
+                            }
+                            if reverse_second {
+                                next_score += reversal_delta(d, order, j + 1, k, path_lengths);
+                            }
                         }
-                        if indices.0 > 0 {
-                            next_score += distance_matrix[order[indices.0] - 1][order[indices.0]];
+                    }
+                    Move::BlockExchange(i, j, len) => {
+                        let d = distance_matrix;
+                        let (prev, next) = (order[i - 1], order[(j + len) % order.len()]);
+                        let (a_first, a_last) = (order[i], order[i + len - 1]);
+                        let (b_first, b_last) = (order[j], order[j + len - 1]);
+                        init_score += d[prev][a_first] + d[b_last][next];
+                        next_score += d[prev][b_first] + d[a_last][next];
+                        if i + len == j {
+                            init_score += d[a_last][b_first];
+                            next_score += d[b_last][a_first];
                         } else {
-                            next_score += distance_matrix[order[order.len() - 1]][order[indices.0]];
+                            let (m_first, m_last) = (order[i + len], order[j - 1]);
+                            init_score += d[a_last][m_first] + d[m_last][b_first];
+                            next_score += d[b_last][m_first] + d[m_last][a_first];
                         }
-                        next_score +=
-                            distance_matrix[order[indices.1]][order[(indices.1 + 1) % order.len()]];
-                        move_type.do_move(order, indices);
                     }
+                    _ => return self.delta_eval_by_eval(mov, order),
+                }
+                if matches!(self, Evaluation::TspPath { .. }) {
+                    init_score += closing_delta(distance_matrix, mov, order);
                 }
                 next_score - init_score
             }
             Evaluation::QAP {
                 distance_matrix,
                 flow_matrix,
+                symmetric,
+            } => match (mov, mov.changed_range()) {
+                (Move::Swap(r, s), _) => {
+                    qap_swap_delta(distance_matrix, flow_matrix, order, *r, *s, *symmetric)
+                }
+                (_, Some((start, end))) => {
+                    // Only the locations between the first and last changed position change
+                    // facility.
+                    let score = |order: &[usize]| {
+                        qap_range_score(
+                            distance_matrix,
+                            flow_matrix,
+                            order,
+                            start,
+                            end + 1,
+                            *symmetric,
+                        )
+                    };
+                    let init_score = score(order);
+                    mov.do_move(order);
+                    let next_score = score(order);
+                    mov.undo_move(order);
+                    next_score - init_score
+                }
+                _ => self.delta_eval_by_eval(mov, order),
+            },
+            Evaluation::TspTw { .. } | Evaluation::Cvrp { .. } => {
+                self.delta_eval_by_eval(mov, order)
+            }
+            Evaluation::FlowShop { .. } => match *mov {
+                Move::Relocate(from, to, len) => {
+                    let removal = self.removal_times(order, from, len);
+                    self.delta_insertion(order, &removal, to)
+                }
+                _ => self.delta_eval_by_eval(mov, order),
+            },
+            Evaluation::LinearOrdering { weight_matrix } => {
+                // Change when `a`, which came before `b`, comes after it.
+                let flip = |a: usize, b: usize| weight_matrix[b][a] - weight_matrix[a][b];
+                match *mov {
+                    Move::Swap(i, j) => {
+                        let (i, j) = (i.min(j), i.max(j));
+                        let (a, b) = (order[i], order[j]);
+                        order[i + 1..j]
+                            .iter()
+                            .map(|m| flip(a, *m) + flip(*m, b))
+                            .sum::<f64>()
+                            + flip(a, b)
+                    }
+                    Move::Reverse(i, j) => (i..j)
+                        .flat_map(|x| (x + 1..=j).map(move |y| (x, y)))
+                        .map(|(x, y)| flip(order[x], order[y]))
+                        .sum(),
+                    Move::Relocate(from, to, len) => {
+                        let segment = &order[from..from + len];
+                        let passed = if from < to {
+                            &order[from + len..to + len]
+                        } else {
+                            &order[to..from]
+                        };
+                        segment
+                            .iter()
+                            .flat_map(|s| passed.iter().map(move |p| (*s, *p)))
+                            .map(|(s, p)| if from < to { flip(s, p) } else { flip(p, s) })
+                            .sum()
+                    }
+                    _ => self.delta_eval_by_eval(mov, order),
+                }
+            }
+            Evaluation::LinearArrangement { .. } => {
+                let mut positions = positions(order);
+                self.delta_arrangement(mov, order, &mut positions)
+            }
+            Evaluation::Custom {
+                delta: Some(delta), ..
+            } => delta(order, mov),
+            Evaluation::Custom { delta: None, .. } => self.delta_eval_by_eval(mov, order),
+        }
+    }
+
+    /// Whether this evaluation goes over the order once with a single level, the fill level of
+    /// the open bin for bin packing or the completion time for a single machine.
+    pub(crate) fn has_prefix_levels(&self) -> bool {
+        matches!(
+            self,
+            Evaluation::Bins { .. }
+                | Evaluation::EmptySpace { .. }
+                | Evaluation::EmptySpaceExp { .. }
+                | Evaluation::SingleMachine { .. }
+        )
+    }
+
+    /// Brings the score so far and level before every position of the order, plus those after
+    /// the last position, up to date from position `start` onward.
+    pub(crate) fn update_prefix_levels(
+        &self,
+        order: &[usize],
+        levels: &mut Vec<(f64, f64)>,
+        start: usize,
+    ) {
+        let start = start.min(levels.len()).max(1);
+        levels.truncate(start);
+        if levels.is_empty() {
+            levels.push((0.0, 0.0));
+        }
+        for i in start..=order.len() {
+            let level = self.prefix_step(order, i - 1, levels[i - 1]);
+            levels.push(level);
+        }
+    }
+
+    /// First position from which the levels after a move changing positions up to `end` can
+    /// be the same as before again.
+    fn prefix_synced(&self, end: usize) -> usize {
+        // Setup times also depend on the job before, which is only the same again after the
+        // position following the move.
+        match self {
+            Evaluation::SingleMachine {
+                setup_times: Some(_),
+                ..
+            } => end + 2,
+            _ => end + 1,
+        }
+    }
+
+    /// Delta of a move given the [prefix levels](Evaluation::update_prefix_levels) of the
+    /// order. The order is only gone over again from the first changed position, until the
+    /// level after the move is the same as before.
+    pub(crate) fn delta_prefix(
+        &self,
+        mov: &Move,
+        order: &mut [usize],
+        levels: &[(f64, f64)],
+    ) -> f64 {
+        let Some((start, end)) = mov.changed_range() else {
+            return self.delta_eval_by_eval(mov, order);
+        };
+        let synced = self.prefix_synced(end);
+        mov.do_move(order);
+        let mut level = levels[start];
+        let mut delta = None;
+        for (i, (score, before)) in levels.iter().enumerate().take(order.len()).skip(start) {
+            if i >= synced && level.1 == *before {
+                delta = Some(level.0 - score);
+                break;
+            }
+            level = self.prefix_step(order, i, level);
+        }
+        mov.undo_move(order);
+        delta.unwrap_or_else(|| self.final_score(level) - self.final_score(levels[order.len()]))
+    }
+
+    /// Delta of a move without the prefix levels. The order is gone over up to the first
+    /// changed position, then before and after the move side by side until their levels are
+    /// the same again.
+    fn delta_prefix_walk(&self, mov: &Move, order: &mut [usize]) -> f64 {
+        let Some((start, end)) = mov.changed_range() else {
+            return self.delta_eval_by_eval(mov, order);
+        };
+        let synced = self.prefix_synced(end);
+        let mut before = (0.0, 0.0);
+        for i in 0..start {
+            before = self.prefix_step(order, i, before);
+        }
+        // The positions stepped over until the levels can be synced, from the job before the
+        // move on, as they were before the move.
+        let offset = start.saturating_sub(1);
+        let old = order[offset..synced.min(order.len())].to_vec();
+        mov.do_move(order);
+        let mut after = before;
+        let mut delta = None;
+        for i in start..order.len() {
+            if i >= synced && after.1 == before.1 {
+                delta = Some(after.0 - before.0);
+                break;
+            }
+            before = if i < synced {
+                self.prefix_step(&old, i - offset, before)
+            } else {
+                self.prefix_step(order, i, before)
+            };
+            after = self.prefix_step(order, i, after);
+        }
+        mov.undo_move(order);
+        delta.unwrap_or_else(|| self.final_score(after) - self.final_score(before))
+    }
+
+    /// Score so far and level after position `i` of the order.
+    fn prefix_step(&self, order: &[usize], i: usize, (score, level): (f64, f64)) -> (f64, f64) {
+        match self {
+            Evaluation::SingleMachine {
+                processing_times,
+                due_dates,
+                weights,
+                setup_times,
             } => {
-                let d = distance_matrix;
-                let f = flow_matrix;
-                let p = order;
-                let r = indices.0;
-                let s = indices.1;
-                let mut delta = 0.0;
-                for i in 0..distance_matrix.len() {
-                    if i == r || i == s {
-                        continue;
+                let job = order[i];
+                let setup = match setup_times {
+                    Some(setup_times) if i > 0 => setup_times[order[i - 1]][job],
+                    _ => 0.0,
+                };
+                let completion = level + setup + processing_times[job];
+                let tardiness = (completion - due_dates[job]).max(0.0);
+                (score + weights[job] * tardiness, completion)
+            }
+            _ => {
+                let (weights, max_fill) = self.bin_parameters();
+                let weight = weights[order[i]];
+                if level + weight > max_fill {
+                    (score + self.closed_bin_score(level), weight)
+                } else {
+                    (score, level + weight)
+                }
+            }
+        }
+    }
+
+    /// Score of the whole order from the score and level after its last position.
+    fn final_score(&self, (score, level): (f64, f64)) -> f64 {
+        match self {
+            Evaluation::SingleMachine { .. } => score,
+            _ => score + self.open_bin_score(level),
+        }
+    }
+
+    fn bin_parameters(&self) -> (&[f64], f64) {
+        match self {
+            Evaluation::Bins { weights, max_fill }
+            | Evaluation::EmptySpace { weights, max_fill }
+            | Evaluation::EmptySpaceExp { weights, max_fill } => (weights, *max_fill),
+            _ => panic!("Only bin packing evaluations have bins"),
+        }
+    }
+
+    /// Score of a bin that is closed with the given fill level.
+    fn closed_bin_score(&self, fill_level: f64) -> f64 {
+        let (_, max_fill) = self.bin_parameters();
+        match self {
+            Evaluation::Bins { .. } => 1.0,
+            Evaluation::EmptySpace { .. } => max_fill - fill_level,
+            _ => (max_fill - fill_level).powf(2.0),
+        }
+    }
+
+    /// Score of the last bin, left open with the given fill level.
+    fn open_bin_score(&self, fill_level: f64) -> f64 {
+        match self {
+            Evaluation::Bins { .. } => 0.0,
+            _ => self.closed_bin_score(fill_level),
+        }
+    }
+
+    /// Whether this evaluation is a TSP tour or path with an asymmetric distance matrix.
+    pub(crate) fn is_asymmetric_tsp(&self) -> bool {
+        matches!(
+            self,
+            Evaluation::Tsp {
+                symmetric: false,
+                ..
+            } | Evaluation::TspPath {
+                symmetric: false,
+                ..
+            }
+        )
+    }
+
+    /// Whether this evaluation is a linear arrangement of a graph.
+    pub(crate) fn is_linear_arrangement(&self) -> bool {
+        matches!(self, Evaluation::LinearArrangement { .. })
+    }
+
+    /// Delta of a move on a linear arrangement given the position of every node, which is
+    /// restored afterwards. For the sum only the edges of the moved nodes are measured, the
+    /// bandwidth is evaluated in full.
+    pub(crate) fn delta_arrangement(
+        &self,
+        mov: &Move,
+        order: &mut [usize],
+        positions: &mut [usize],
+    ) -> f64 {
+        let Evaluation::LinearArrangement {
+            neighbors,
+            objective,
+        } = self
+        else {
+            panic!("Only linear arrangements have arrangement deltas")
+        };
+        let (ArrangementObjective::Sum, Some((start, end))) = (objective, mov.changed_range())
+        else {
+            return self.delta_eval_by_eval(mov, order);
+        };
+        // A swap leaves the nodes between both positions in place.
+        let moved: Vec<usize> = match *mov {
+            Move::Swap(i, j) => vec![order[i], order[j]],
+            _ => order[start..=end].to_vec(),
+        };
+        let length = |positions: &[usize]| {
+            let mut length = 0.0;
+            for x in &moved {
+                for (y, weight) in &neighbors[*x] {
+                    // Edges between two moved nodes are met from both sides.
+                    let both = match mov {
+                        Move::Swap(..) => moved.contains(y),
+                        _ => (start..=end).contains(&positions[*y]),
+                    };
+                    let share = if both { 0.5 } else { 1.0 };
+                    length += share * weight * positions[*x].abs_diff(positions[*y]) as f64;
+                }
+            }
+            length
+        };
+        let before = length(positions);
+        mov.do_move(order);
+        for (i, node) in order[start..=end].iter().enumerate() {
+            positions[*node] = start + i;
+        }
+        let after = length(positions);
+        mov.undo_move(order);
+        for (i, node) in order[start..=end].iter().enumerate() {
+            positions[*node] = start + i;
+        }
+        after - before
+    }
+
+    /// Whether the move type keeps the fixed start and end of a TSP path in place.
+    pub(crate) fn fixed_ends_kept_by(&self, move_type: &MoveType) -> bool {
+        let Evaluation::TspPath { start, end, .. } = self else {
+            return true;
+        };
+        let (keeps_start, keeps_end) = move_type.keeps_ends();
+        (start.is_none() || keeps_start) && (end.is_none() || keeps_end)
+    }
+
+    /// Puts the fixed start and end node of a TSP path on the first and last position.
+    pub(crate) fn place_fixed_ends(&self, order: &mut [usize]) {
+        let Evaluation::TspPath { start, end, .. } = self else {
+            return;
+        };
+        let last = order.len() - 1;
+        for (node, target) in [(start, 0), (end, last)] {
+            if let Some(node) = node {
+                let i = order.iter().position(|other| other == node).unwrap();
+                order.swap(i, target);
+            }
+        }
+    }
+
+    /// Checks that an order of a TSP path starts and ends at the fixed nodes.
+    pub(crate) fn check_fixed_ends(&self, order: &[usize]) -> Result<(), String> {
+        let Evaluation::TspPath { start, end, .. } = self else {
+            return Ok(());
+        };
+        if let Some(start) = start.filter(|start| order.first() != Some(start)) {
+            return Err(format!("The path has to start at node {start}"));
+        }
+        if let Some(end) = end.filter(|end| order.last() != Some(end)) {
+            return Err(format!("The path has to end at node {end}"));
+        }
+        Ok(())
+    }
+
+    /// Brings the length of the path up to every position of a TSP order, walked forwards
+    /// and backwards, up to date from position `start` onward.
+    pub(crate) fn update_path_lengths(
+        &self,
+        order: &[usize],
+        path_lengths: &mut Vec<(f64, f64)>,
+        start: usize,
+    ) {
+        let (Evaluation::Tsp {
+            distance_matrix, ..
+        }
+        | Evaluation::TspPath {
+            distance_matrix, ..
+        }) = self
+        else {
+            panic!("Only TSP evaluations have path lengths")
+        };
+        let start = start.min(path_lengths.len()).max(1);
+        path_lengths.truncate(start);
+        if path_lengths.is_empty() {
+            path_lengths.push((0.0, 0.0));
+        }
+        for i in start..order.len() {
+            let (forward, backward) = path_lengths[i - 1];
+            path_lengths.push((
+                forward + distance_matrix[order[i - 1]][order[i]],
+                backward + distance_matrix[order[i]][order[i - 1]],
+            ));
+        }
+    }
+
+    /// Brings the delta of every swap `(u, v)` with `u < v` of a QAP up to date. After a swap
+    /// of `swapped` only the swaps sharing a location with it take O(n) time, the others are
+    /// updated in O(1) time, otherwise all deltas are computed again.
+    pub(crate) fn update_swap_deltas(
+        &self,
+        order: &[usize],
+        swap_deltas: &mut Vec<Vec<f64>>,
+        swapped: Option<(usize, usize)>,
+    ) {
+        let Evaluation::QAP {
+            distance_matrix: d,
+            flow_matrix: f,
+            symmetric,
+        } = self
+        else {
+            panic!("Only QAP evaluations have swap deltas")
+        };
+        let n = order.len();
+        let swapped = swapped.filter(|_| !swap_deltas.is_empty());
+        if swap_deltas.is_empty() {
+            *swap_deltas = vec![vec![0.0; n]; n];
+        }
+        let p = order;
+        for u in 0..n {
+            for v in u + 1..n {
+                swap_deltas[u][v] = match swapped {
+                    Some((r, s)) if u != r && u != s && v != r && v != s => {
+                        let (pr, ps, pu, pv) = (p[r], p[s], p[u], p[v]);
+                        let change = (d[r][u] - d[r][v] + d[s][v] - d[s][u])
+                            * (f[ps][pu] - f[ps][pv] + f[pr][pv] - f[pr][pu])
+                            + (d[u][r] - d[v][r] + d[v][s] - d[u][s])
+                                * (f[pu][ps] - f[pv][ps] + f[pv][pr] - f[pu][pr]);
+                        // Symmetric instances count every pair once.
+                        swap_deltas[u][v] + if *symmetric { change / 2.0 } else { change }
                     }
-                    delta += (d[s][i] - d[r][i]) * (f[p[r]][p[i]] - f[p[s]][p[i]]);
+                    _ => qap_swap_delta(d, f, p, u, v, *symmetric),
+                };
+            }
+        }
+    }
+
+    /// Travel, lateness and every visit of a tour with time windows.
+    pub(crate) fn time_window_report(&self, order: &[usize]) -> Result<TimeWindowReport, String> {
+        if !matches!(self, Evaluation::TspTw { .. }) {
+            return Err(
+                "Only TSP evaluations with time windows have a feasibility report".to_string(),
+            );
+        }
+        let mut visits = Vec::with_capacity(order.len() + 1);
+        let (travel, lateness) = self.walk_time_windows(order, |visit| visits.push(visit));
+        Ok(TimeWindowReport {
+            travel,
+            lateness,
+            visits,
+        })
+    }
+
+    /// Goes over a tour with time windows from the depot, gives every visit to `visit` and
+    /// returns the travel and summed lateness.
+    fn walk_time_windows(&self, order: &[usize], mut visit: impl FnMut(Visit)) -> (f64, f64) {
+        let Evaluation::TspTw {
+            distance_matrix,
+            time_windows,
+            service_times,
+            ..
+        } = self
+        else {
+            panic!("Only TSP evaluations with time windows have time windows")
+        };
+        let depot = order.iter().position(|node| *node == 0).unwrap_or(0);
+        let (mut travel, mut lateness) = (0.0, 0.0);
+        let mut time = time_windows[0].0 + service_times[0];
+        let mut previous = 0;
+        for i in 1..=order.len() {
+            let node = order[(depot + i) % order.len()];
+            travel += distance_matrix[previous][node];
+            let arrival = time + distance_matrix[previous][node];
+            let (open, close) = time_windows[node];
+            let start = arrival.max(open);
+            let late = (arrival - close).max(0.0);
+            lateness += late;
+            visit(Visit {
+                node,
+                arrival,
+                start,
+                lateness: late,
+            });
+            time = start + service_times[node];
+            previous = node;
+        }
+        (travel, lateness)
+    }
+
+    /// Routes of a CVRP giant tour, every route as the nodes it visits after leaving the depot.
+    /// Empty routes are left out.
+    pub(crate) fn routes(&self, order: &[usize]) -> Result<Vec<Vec<usize>>, String> {
+        let Evaluation::Cvrp {
+            distance_matrix,
+            vehicles,
+            ..
+        } = self
+        else {
+            return Err("Only CVRP evaluations have routes".to_string());
+        };
+        let customers = distance_matrix.len() - 1;
+        let node = |element: &usize| element + 1;
+        let routes: Vec<Vec<usize>> = match vehicles {
+            Some(_) => order
+                .split(|element| *element >= customers)
+                .map(|route| route.iter().map(node).collect())
+                .collect(),
+            None => {
+                let (_, predecessors) = self.split(order);
+                let mut routes = vec![];
+                let mut end = order.len();
+                while end > 0 {
+                    let start = predecessors[end];
+                    routes.push(order[start..end].iter().map(node).collect());
+                    end = start;
                 }
-                delta
+                routes.reverse();
+                routes
+            }
+        };
+        Ok(routes
+            .into_iter()
+            .filter(|route| !route.is_empty())
+            .collect())
+    }
+
+    /// Prins' split of a CVRP giant tour: the lowest score of cutting it into routes and the
+    /// start of the last route ending before every position.
+    fn split(&self, order: &[usize]) -> (f64, Vec<usize>) {
+        let Evaluation::Cvrp {
+            distance_matrix: d,
+            demands,
+            capacity,
+            penalty,
+            ..
+        } = self
+        else {
+            panic!("Only CVRP evaluations can be split")
+        };
+        let n = order.len();
+        let mut scores = vec![f64::INFINITY; n + 1];
+        let mut predecessors = vec![0; n + 1];
+        scores[0] = 0.0;
+        for start in 0..n {
+            let (mut load, mut path, mut previous) = (0.0, 0.0, 0);
+            for end in start..n {
+                let node = order[end] + 1;
+                load += demands[node];
+                // A customer over capacity on its own still needs a route.
+                if end > start && load > *capacity {
+                    break;
+                }
+                path += d[previous][node];
+                previous = node;
+                let score =
+                    scores[start] + path + d[node][0] + penalty * (load - capacity).max(0.0);
+                if score < scores[end + 1] {
+                    scores[end + 1] = score;
+                    predecessors[end + 1] = start;
+                }
+            }
+        }
+        (scores[n], predecessors)
+    }
+
+    /// Whether this evaluation is a flow shop.
+    pub(crate) fn is_flow_shop(&self) -> bool {
+        matches!(self, Evaluation::FlowShop { .. })
+    }
+
+    /// Cost of a flow shop job completing at `completion`, 0 for the makespan.
+    fn job_cost(&self, job: usize, completion: f64) -> f64 {
+        let Evaluation::FlowShop {
+            objective,
+            due_dates,
+            weights,
+            ..
+        } = self
+        else {
+            panic!("Only flow shop evaluations have job costs")
+        };
+        match objective {
+            FlowShopObjective::Makespan => 0.0,
+            FlowShopObjective::TotalFlowTime => weights[job] * completion,
+            FlowShopObjective::TotalTardiness => {
+                weights[job] * (completion - due_dates[job]).max(0.0)
             }
         }
     }
 
+    /// Completion times of a flow shop order with the segment of `len` jobs at `from` taken
+    /// out, in O(jobs * machines) time.
+    pub(crate) fn removal_times(&self, order: &[usize], from: usize, len: usize) -> RemovalTimes {
+        let Evaluation::FlowShop {
+            processing_times, ..
+        } = self
+        else {
+            panic!("Only flow shop evaluations have removal times")
+        };
+        let machines = processing_times[0].len();
+        let rest: Vec<usize> = order[..from]
+            .iter()
+            .chain(&order[from + len..])
+            .copied()
+            .collect();
+        let mut heads = Vec::with_capacity(rest.len());
+        let mut costs = Vec::with_capacity(rest.len() + 1);
+        let mut completion = vec![0.0; machines];
+        costs.push(0.0);
+        for job in &rest {
+            schedule_job(&processing_times[*job], &mut completion);
+            heads.push(completion.clone());
+            costs.push(costs[costs.len() - 1] + self.job_cost(*job, completion[machines - 1]));
+        }
+        let mut tails = vec![vec![0.0; machines]; rest.len() + 1];
+        for (i, job) in rest.iter().enumerate().rev() {
+            let mut after: f64 = 0.0;
+            for machine in (0..machines).rev() {
+                after = after.max(tails[i + 1][machine]) + processing_times[*job][machine];
+                tails[i][machine] = after;
+            }
+        }
+        RemovalTimes {
+            from,
+            len,
+            heads,
+            tails,
+            costs,
+            score: self.eval(order),
+        }
+    }
+
+    /// Delta of putting the removed segment of a flow shop order back before position `to`
+    /// of the remaining jobs, the makespan only schedules the segment itself.
+    pub(crate) fn delta_insertion(
+        &self,
+        order: &[usize],
+        removal: &RemovalTimes,
+        to: usize,
+    ) -> f64 {
+        let Evaluation::FlowShop {
+            processing_times,
+            objective,
+            ..
+        } = self
+        else {
+            panic!("Only flow shop evaluations have insertion deltas")
+        };
+        let machines = processing_times[0].len();
+        let (from, len) = (removal.from, removal.len);
+        let mut completion = match to {
+            0 => vec![0.0; machines],
+            _ => removal.heads[to - 1].clone(),
+        };
+        let mut score = removal.costs[to];
+        for job in &order[from..from + len] {
+            schedule_job(&processing_times[*job], &mut completion);
+            score += self.job_cost(*job, completion[machines - 1]);
+        }
+        let score = match objective {
+            FlowShopObjective::Makespan => completion
+                .iter()
+                .zip(&removal.tails[to])
+                .map(|(head, tail)| head + tail)
+                .fold(f64::MIN, f64::max),
+            _ => {
+                for i in to..order.len() - len {
+                    let job = if i < from { order[i] } else { order[i + len] };
+                    schedule_job(&processing_times[job], &mut completion);
+                    score += self.job_cost(job, completion[machines - 1]);
+                }
+                score
+            }
+        };
+        score - removal.score
+    }
+
+    /// Fallback delta evaluation: evaluates the whole order before and after the move.
+    fn delta_eval_by_eval(&self, mov: &Move, order: &mut [usize]) -> f64 {
+        let first = self.eval(order);
+        if let Move::Custom(custom) = mov {
+            let mut next = order.to_vec();
+            custom.do_move(&mut next);
+            return self.eval(&next) - first;
+        }
+        mov.do_move(order);
+        let sec = self.eval(order);
+        mov.undo_move(order);
+        sec - first
+    }
+
     pub(crate) fn eval(&self, order: &[usize]) -> f64 {
         match self {
             Evaluation::Bins { weights, max_fill } => {
@@ -226,20 +1286,130 @@ impl Evaluation {
                 score += distance_matrix[order[order.len() - 1]][order[0]];
                 score
             }
+            Evaluation::TspPath {
+                distance_matrix, ..
+            } => order
+                .windows(2)
+                .map(|edge| distance_matrix[edge[0]][edge[1]])
+                .sum(),
             Evaluation::QAP {
                 distance_matrix,
                 flow_matrix,
+                symmetric,
+            } => qap_range_score(
+                distance_matrix,
+                flow_matrix,
+                order,
+                0,
+                order.len(),
+                *symmetric,
+            ),
+            Evaluation::TspTw { penalty, .. } => {
+                let (travel, lateness) = self.walk_time_windows(order, |_| {});
+                travel + penalty * lateness
+            }
+            Evaluation::Cvrp {
+                distance_matrix: d,
+                demands,
+                capacity,
+                vehicles: Some(_),
+                penalty,
+            } => {
+                let customers = d.len() - 1;
+                let (mut score, mut load, mut previous) = (0.0, 0.0, 0);
+                for element in order.iter().chain([&customers]) {
+                    // Separators and the end of the tour go back to the depot.
+                    let node = if *element < customers { element + 1 } else { 0 };
+                    score += d[previous][node];
+                    if node == 0 {
+                        score += penalty * (load - capacity).max(0.0);
+                        load = 0.0;
+                    } else {
+                        load += demands[node];
+                    }
+                    previous = node;
+                }
+                score
+            }
+            Evaluation::Cvrp { .. } => self.split(order).0,
+            Evaluation::SingleMachine { .. } => {
+                let mut level = (0.0, 0.0);
+                for i in 0..order.len() {
+                    level = self.prefix_step(order, i, level);
+                }
+                level.0
+            }
+            Evaluation::FlowShop {
+                processing_times,
+                objective,
+                ..
             } => {
-                let mut value = 0.0;
-                for i in 0..distance_matrix.len() {
-                    for j in (i + 1)..distance_matrix.len() {
-                        value += distance_matrix[i][j] * flow_matrix[order[i]][order[j]];
+                let mut completion = vec![0.0; processing_times[0].len()];
+                let mut score = 0.0;
+                for job in order {
+                    schedule_job(&processing_times[*job], &mut completion);
+                    score += self.job_cost(*job, completion[completion.len() - 1]);
+                }
+                match objective {
+                    FlowShopObjective::Makespan => completion[completion.len() - 1],
+                    _ => score,
+                }
+            }
+            Evaluation::LinearOrdering { weight_matrix } => {
+                let mut score = 0.0;
+                for (i, a) in order.iter().enumerate() {
+                    for b in &order[i + 1..] {
+                        score += weight_matrix[*a][*b];
                     }
                 }
-                value
+                score
+            }
+            Evaluation::LinearArrangement {
+                neighbors,
+                objective,
+            } => {
+                let positions = &positions(order);
+                let lengths = neighbors.iter().enumerate().flat_map(|(x, edges)| {
+                    edges
+                        .iter()
+                        .filter(move |(y, _)| x < *y)
+                        .map(move |(y, weight)| {
+                            weight * positions[x].abs_diff(positions[*y]) as f64
+                        })
+                });
+                match objective {
+                    ArrangementObjective::Sum => lengths.sum(),
+                    ArrangementObjective::Bandwidth => lengths.fold(0.0, f64::max),
+                }
             }
+            Evaluation::Custom { eval, .. } => eval(order),
         }
     }
+
+    /// The `k` closest cities of every city of a TSP, closest first, `None` for other
+    /// evaluations.
+    pub(crate) fn nearest_neighbors(&self, k: usize) -> Option<Vec<Vec<usize>>> {
+        let (Evaluation::Tsp {
+            distance_matrix, ..
+        }
+        | Evaluation::TspPath {
+            distance_matrix, ..
+        }) = self
+        else {
+            return None;
+        };
+        let len = distance_matrix.len();
+        let neighbors = (0..len)
+            .map(|city| {
+                let mut others: Vec<usize> = (0..len).filter(|other| *other != city).collect();
+                let distances = &distance_matrix[city];
+                others.sort_by(|a, b| distances[*a].total_cmp(&distances[*b]));
+                others.truncate(k);
+                others
+            })
+            .collect();
+        Some(neighbors)
+    }
     pub(crate) fn length(&self) -> usize {
         match self {
             Evaluation::Bins {
@@ -258,37 +1428,161 @@ impl Evaluation {
                 distance_matrix,
                 symmetric: _,
             } => distance_matrix.len(),
+            Evaluation::TspPath {
+                distance_matrix, ..
+            } => distance_matrix.len(),
             Evaluation::QAP {
-                distance_matrix,
-                flow_matrix: _,
+                distance_matrix, ..
+            } => distance_matrix.len(),
+            Evaluation::TspTw {
+                distance_matrix, ..
             } => distance_matrix.len(),
+            Evaluation::Cvrp {
+                distance_matrix,
+                vehicles,
+                ..
+            } => distance_matrix.len() - 1 + vehicles.map_or(0, |vehicles| vehicles - 1),
+            Evaluation::SingleMachine {
+                processing_times, ..
+            } => processing_times.len(),
+            Evaluation::FlowShop {
+                processing_times, ..
+            } => processing_times.len(),
+            Evaluation::LinearOrdering { weight_matrix } => weight_matrix.len(),
+            Evaluation::LinearArrangement { neighbors, .. } => neighbors.len(),
+            Evaluation::Custom { size, .. } => *size,
         }
     }
 }
 
+/// Schedules a flow shop job after the jobs with the given completion times on every machine,
+/// which become the completion times of the job.
+fn schedule_job(processing_times: &[f64], completion: &mut [f64]) {
+    let mut ready: f64 = 0.0;
+    for (machine, time) in completion.iter_mut().zip(processing_times) {
+        ready = ready.max(*machine) + time;
+        *machine = ready;
+    }
+}
+
+/// Change in length of the edge from the last node of a tour back to the first one when the
+/// move is performed.
+fn closing_delta(distance_matrix: &[Vec<f64>], mov: &Move, order: &mut [usize]) -> f64 {
+    let last = order.len() - 1;
+    match mov.changed_range() {
+        Some((start, end)) if start > 0 && end < last => 0.0,
+        _ => {
+            let before = distance_matrix[order[last]][order[0]];
+            mov.do_move(order);
+            let after = distance_matrix[order[last]][order[0]];
+            mov.undo_move(order);
+            after - before
+        }
+    }
+}
+
+/// Change in length of the path `order[start..=end]` when it is walked backwards, looked up
+/// in the path lengths if given.
+fn reversal_delta(
+    distance_matrix: &[Vec<f64>],
+    order: &[usize],
+    start: usize,
+    end: usize,
+    path_lengths: Option<&[(f64, f64)]>,
+) -> f64 {
+    if let Some(lengths) = path_lengths {
+        let (forward, backward) = (
+            lengths[end].0 - lengths[start].0,
+            lengths[end].1 - lengths[start].1,
+        );
+        return backward - forward;
+    }
+    (start..end)
+        .map(|i| distance_matrix[order[i + 1]][order[i]] - distance_matrix[order[i]][order[i + 1]])
+        .sum()
+}
+
+/// Part of the QAP score involving at least one location in `start..end`.
+fn qap_range_score(
+    distance_matrix: &[Vec<f64>],
+    flow_matrix: &[Vec<f64>],
+    order: &[usize],
+    start: usize,
+    end: usize,
+    symmetric: bool,
+) -> f64 {
+    let (d, f) = (distance_matrix, flow_matrix);
+    let mut score = 0.0;
+    for i in start..end {
+        if !symmetric {
+            score += d[i][i] * f[order[i]][order[i]];
+        }
+        for j in (0..start).chain(i + 1..order.len()) {
+            if symmetric {
+                let (a, b) = (i.min(j), i.max(j));
+                score += d[a][b] * f[order[a]][order[b]];
+            } else {
+                score += d[i][j] * f[order[i]][order[j]] + d[j][i] * f[order[j]][order[i]];
+            }
+        }
+    }
+    score
+}
+
+/// Change in QAP score when the facilities of locations `r` and `s` are swapped, Taillard's
+/// formula in O(n) time.
+fn qap_swap_delta(
+    distance_matrix: &[Vec<f64>],
+    flow_matrix: &[Vec<f64>],
+    order: &[usize],
+    r: usize,
+    s: usize,
+    symmetric: bool,
+) -> f64 {
+    let (d, f, p) = (distance_matrix, flow_matrix, order);
+    let (pr, ps) = (p[r], p[s]);
+    let mut delta = 0.0;
+    if !symmetric {
+        delta += (d[r][r] - d[s][s]) * (f[ps][ps] - f[pr][pr])
+            + (d[r][s] - d[s][r]) * (f[ps][pr] - f[pr][ps]);
+    }
+    for k in 0..p.len() {
+        if k == r || k == s {
+            continue;
+        }
+        let pk = p[k];
+        if symmetric {
+            delta += (d[s][k] - d[r][k]) * (f[pr][pk] - f[ps][pk]);
+        } else {
+            delta += (d[k][r] - d[k][s]) * (f[pk][ps] - f[pk][pr])
+                + (d[r][k] - d[s][k]) * (f[ps][pk] - f[pr][pk]);
+        }
+    }
+    delta
+}
+
 #[cfg(test)]
 mod tests {
+    use std::sync::{Arc, Mutex};
     use std::vec;
 
-    use rand::{rngs::SmallRng, SeedableRng};
+    use crate::local_search::{LocalSearch, TabuSearch};
+    use crate::termination::TerminationFunction;
 
-    use crate::MoveType;
+    use crate::problem::{ArrayProblem, Move, MoveType, Problem, State};
 
-    use super::Evaluation;
+    use super::{ArrangementObjective, Evaluation, FlowShopObjective};
     #[test]
     fn empty_space_test() {
         let eval = Evaluation::EmptySpace {
             weights: vec![2.0, 5.0, 4.0, 7.0, 1.0, 3.0, 8.0],
             max_fill: 10.0,
         };
-        let swap_move = &MoveType::Swap {
-            rng: Box::new(SmallRng::seed_from_u64(0)),
-            size: 7,
-        };
         let mut array: Vec<usize> = (0..7).collect();
         let score_0 = eval.eval(&array);
-        let delta = eval.delta_eval((0, 3), swap_move, &mut array);
-        swap_move.do_move(&mut array, (0, 3));
+        let swap_move = Move::Swap(0, 3);
+        let delta = eval.delta_eval(&swap_move, &mut array);
+        swap_move.do_move(&mut array);
         let score_1 = eval.eval(&array);
         assert_eq!(score_0, 20.0);
         assert_eq!(delta, score_1 - score_0);
@@ -299,14 +1593,11 @@ mod tests {
             weights: vec![2.0, 5.0, 4.0, 7.0, 1.0, 3.0, 8.0],
             max_fill: 10.0,
         };
-        let swap_move = &MoveType::Swap {
-            rng: Box::new(SmallRng::seed_from_u64(0)),
-            size: 7,
-        };
         let mut array: Vec<usize> = (0..7).collect();
         let score_0 = eval.eval(&array);
-        let delta = eval.delta_eval((0, 3), swap_move, &mut array);
-        swap_move.do_move(&mut array, (0, 3));
+        let swap_move = Move::Swap(0, 3);
+        let delta = eval.delta_eval(&swap_move, &mut array);
+        swap_move.do_move(&mut array);
         let score_1 = eval.eval(&array);
         assert_eq!(score_0, 4.0);
         assert_eq!(delta, score_1 - score_0);
@@ -317,14 +1608,11 @@ mod tests {
             weights: vec![2.0, 5.0, 4.0, 7.0, 1.0, 3.0, 8.0],
             max_fill: 10.0,
         };
-        let swap_move = &MoveType::Swap {
-            rng: Box::new(SmallRng::seed_from_u64(0)),
-            size: 7,
-        };
         let mut array: Vec<usize> = (0..7).collect();
         let score_0 = eval.eval(&array);
-        let delta = eval.delta_eval((0, 3), swap_move, &mut array);
-        swap_move.do_move(&mut array, (0, 3));
+        let swap_move = Move::Swap(0, 3);
+        let delta = eval.delta_eval(&swap_move, &mut array);
+        swap_move.do_move(&mut array);
         let score_1 = eval.eval(&array);
         assert_eq!(score_0, 102.0);
         assert_eq!(delta, score_1 - score_0);
@@ -341,18 +1629,14 @@ mod tests {
             distance_matrix,
             symmetric: true,
         };
-        let swap_move = &MoveType::Swap {
-            rng: Box::new(SmallRng::seed_from_u64(0)),
-            size: 4,
-        };
-        let tests = vec![(1, 2), (0, 2), (0, 3)];
+        let tests = vec![Move::Swap(1, 2), Move::Swap(0, 2), Move::Swap(0, 3)];
         let mut array: Vec<usize> = (0..4).collect();
         for test_move in tests {
             let score_0 = eval.eval(&array);
-            let delta = eval.delta_eval(test_move, swap_move, &mut array);
-            swap_move.do_move(&mut array, test_move);
+            let delta = eval.delta_eval(&test_move, &mut array);
+            test_move.do_move(&mut array);
             let score_1 = eval.eval(&array);
-            swap_move.do_move(&mut array, test_move);
+            test_move.undo_move(&mut array);
             assert_eq!(delta, score_1 - score_0);
         }
     }
@@ -373,20 +1657,736 @@ mod tests {
         let eval = Evaluation::QAP {
             distance_matrix,
             flow_matrix,
+            symmetric: true,
         };
-        let swap_move = &MoveType::Swap {
-            rng: Box::new(SmallRng::seed_from_u64(0)),
-            size: 4,
-        };
-        let tests = vec![(1, 2), (0, 2), (0, 3)];
+        let tests = vec![Move::Swap(1, 2), Move::Swap(0, 2), Move::Swap(0, 3)];
         let mut array: Vec<usize> = (0..4).collect();
+        // Every pair of locations is counted once: 2 * 2 + 4 * 4 + 3 * 8.
+        assert_eq!(eval.eval(&array), 44.0);
         for test_move in tests {
             let score_0 = eval.eval(&array);
-            let delta = eval.delta_eval(test_move, swap_move, &mut array);
-            swap_move.do_move(&mut array, test_move);
+            let delta = eval.delta_eval(&test_move, &mut array);
+            test_move.do_move(&mut array);
             let score_1 = eval.eval(&array);
-            swap_move.do_move(&mut array, test_move);
+            test_move.undo_move(&mut array);
             assert_eq!(delta, score_1 - score_0);
         }
     }
+    #[test]
+    fn asymmetric_qap_test() {
+        let distance_matrix: Vec<Vec<f64>> = vec![
+            vec![1.0, 2.0, 5.0, 8.0, 3.0],
+            vec![6.0, 0.0, 4.0, 1.0, 9.0],
+            vec![5.0, 1.0, 2.0, 7.0, 2.0],
+            vec![4.0, 1.0, 3.0, 0.0, 6.0],
+            vec![7.0, 8.0, 2.0, 5.0, 3.0],
+        ];
+        let flow_matrix = vec![
+            vec![2.0, 2.0, 0.0, 1.0, 3.0],
+            vec![1.0, 0.0, 4.0, 0.0, 5.0],
+            vec![0.0, 3.0, 1.0, 8.0, 1.0],
+            vec![1.0, 0.0, 2.0, 0.0, 6.0],
+            vec![4.0, 5.0, 1.0, 7.0, 1.0],
+        ];
+        let eval = Evaluation::qap(distance_matrix.clone(), flow_matrix.clone());
+        let order = [2, 0, 4, 1, 3];
+        let mut score = 0.0;
+        for i in 0..5 {
+            for j in 0..5 {
+                score += distance_matrix[i][j] * flow_matrix[order[i]][order[j]];
+            }
+        }
+        assert_eq!(eval.eval(&order), score);
+
+        let move_type = MoveType::multi_neighbor(
+            vec![
+                MoveType::swap(Some(0)),
+                MoveType::reverse(Some(0)),
+                MoveType::or_opt(Some(0)),
+                MoveType::three_opt(Some(0)),
+            ],
+            None,
+            None,
+        );
+        let mut problem = ArrayProblem::new(&move_type, &eval);
+        let mut array = order.to_vec();
+        for test_move in problem.get_all_mov() {
+            let score_0 = eval.eval(&array);
+            let delta = eval.delta_eval(&test_move, &mut array);
+            test_move.do_move(&mut array);
+            let score_1 = eval.eval(&array);
+            assert!((delta - (score_1 - score_0)).abs() < 1e-9);
+        }
+    }
+    #[test]
+    fn qap_swap_deltas_test() {
+        let symmetric = vec![
+            vec![0.0, 2.0, 9.0, 5.0, 1.0],
+            vec![2.0, 0.0, 4.0, 6.0, 3.0],
+            vec![9.0, 4.0, 0.0, 3.0, 7.0],
+            vec![5.0, 6.0, 3.0, 0.0, 2.0],
+            vec![1.0, 3.0, 7.0, 2.0, 0.0],
+        ];
+        let asymmetric = vec![
+            vec![1.0, 2.0, 5.0, 8.0, 3.0],
+            vec![6.0, 0.0, 4.0, 1.0, 9.0],
+            vec![5.0, 1.0, 2.0, 7.0, 2.0],
+            vec![4.0, 1.0, 3.0, 0.0, 6.0],
+            vec![7.0, 8.0, 2.0, 5.0, 3.0],
+        ];
+        let evals = [
+            Evaluation::qap(symmetric.clone(), symmetric.clone()),
+            Evaluation::qap(asymmetric.clone(), symmetric),
+            Evaluation::qap(asymmetric.clone(), asymmetric),
+        ];
+        for eval in evals {
+            let mut problem = ArrayProblem::new(&MoveType::swap(Some(0)), &eval);
+            for _ in 0..4 {
+                // Searching all moves keeps the swap deltas up to date.
+                let moves = problem.get_all_mov();
+                for test_move in &moves {
+                    let score_0 = problem.eval();
+                    let delta = problem.delta_eval(test_move);
+                    problem.do_mov(test_move);
+                    assert!((delta - (problem.eval() - score_0)).abs() < 1e-9);
+                    problem.undo_mov(test_move);
+                }
+                problem.do_mov(&moves[3]);
+            }
+        }
+    }
+    #[test]
+    fn tsp_tw_test() {
+        let positions: [f64; 4] = [0.0, 1.0, 2.0, 3.0];
+        let distance_matrix: Vec<Vec<f64>> = positions
+            .iter()
+            .map(|a| positions.iter().map(|b| (a - b).abs()).collect())
+            .collect();
+        let time_windows = vec![(0.0, 100.0), (0.0, 0.5), (5.0, 6.0), (0.0, 100.0)];
+        let eval = Evaluation::tsp_tw(
+            distance_matrix,
+            time_windows,
+            Some(vec![0.0, 1.0, 0.0, 0.0]),
+            Some(10.0),
+        );
+        // Node 1 is reached 0.5 late, node 2 is waited for until 5.
+        assert_eq!(eval.eval(&[0, 1, 2, 3]), 6.0 + 5.0);
+        let report = eval.time_window_report(&[2, 3, 0, 1]).unwrap();
+        assert!(!report.is_feasible());
+        assert_eq!((report.travel, report.lateness), (6.0, 0.5));
+        let arrivals: Vec<(usize, f64, f64)> = report
+            .visits
+            .iter()
+            .map(|visit| (visit.node, visit.arrival, visit.start))
+            .collect();
+        assert_eq!(
+            arrivals,
+            [(1, 1.0, 1.0), (2, 3.0, 5.0), (3, 6.0, 6.0), (0, 9.0, 9.0)]
+        );
+        assert!(Evaluation::tsp(vec![vec![0.0]])
+            .time_window_report(&[0])
+            .is_err());
+    }
+    #[test]
+    fn tsp_tw_tabu_search_test() {
+        let positions: [f64; 4] = [0.0, 1.0, 2.0, 3.0];
+        let distance_matrix: Vec<Vec<f64>> = positions
+            .iter()
+            .map(|a| positions.iter().map(|b| (a - b).abs()).collect())
+            .collect();
+        // Only going to node 3 first is on time.
+        let time_windows = vec![(0.0, 100.0), (6.0, 7.0), (0.0, 100.0), (0.0, 3.5)];
+        let eval = Evaluation::tsp_tw(distance_matrix, time_windows, None, None);
+        let array_problem = Arc::new(Mutex::new(ArrayProblem::new(
+            &MoveType::swap(Some(0)),
+            &eval,
+        )));
+        let problem: Arc<Mutex<dyn Problem>> = array_problem.clone();
+        let termination = TerminationFunction::max_iterations(20);
+        let mut sim = TabuSearch::new(&problem, &termination, true, None);
+        let data = sim.run(false, None).unwrap();
+        assert_eq!(data.last().unwrap().1, 6.0);
+        let array_problem = array_problem.lock().unwrap();
+        let best = array_problem.get_best_solution().unwrap();
+        assert!(array_problem
+            .time_window_report(&best)
+            .unwrap()
+            .is_feasible());
+    }
+    #[test]
+    fn cvrp_test() {
+        // Depot in the middle of a line, two customers on either side.
+        let positions: [f64; 5] = [0.0, 1.0, 2.0, -1.0, -2.0];
+        let distance_matrix: Vec<Vec<f64>> = positions
+            .iter()
+            .map(|a| positions.iter().map(|b| (a - b).abs()).collect())
+            .collect();
+        let demands = vec![0.0, 1.0, 1.0, 1.0, 1.0];
+        let split = Evaluation::cvrp(distance_matrix.clone(), demands.clone(), 2.0, None, None);
+        assert_eq!(split.length(), 4);
+        assert_eq!(split.eval(&[0, 1, 2, 3]), 8.0);
+        assert_eq!(
+            split.routes(&[0, 1, 2, 3]).unwrap(),
+            [vec![1, 2], vec![3, 4]]
+        );
+        assert_eq!(split.eval(&[0, 2, 1, 3]), 12.0);
+
+        let separators = Evaluation::cvrp(distance_matrix, demands, 2.0, Some(3), Some(10.0));
+        assert_eq!(separators.length(), 6);
+        assert_eq!(separators.eval(&[0, 1, 4, 2, 3, 5]), 8.0);
+        assert_eq!(
+            separators.routes(&[4, 0, 1, 5, 2, 3]).unwrap(),
+            [vec![1, 2], vec![3, 4]]
+        );
+        // One route over capacity by 2.
+        assert_eq!(separators.eval(&[0, 1, 2, 3, 4, 5]), 8.0 + 20.0);
+        assert!(Evaluation::tsp(vec![vec![0.0]]).routes(&[0]).is_err());
+    }
+    #[test]
+    fn cvrp_tabu_search_test() {
+        let positions: [f64; 7] = [0.0, 1.0, 2.0, 3.0, -1.0, -2.0, -3.0];
+        let distance_matrix: Vec<Vec<f64>> = positions
+            .iter()
+            .map(|a| positions.iter().map(|b| (a - b).abs()).collect())
+            .collect();
+        let demands = vec![0.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0];
+        for vehicles in [None, Some(2)] {
+            let eval = Evaluation::cvrp(
+                distance_matrix.clone(),
+                demands.clone(),
+                3.0,
+                vehicles,
+                None,
+            );
+            let move_type = MoveType::multi_neighbor(
+                vec![MoveType::swap(Some(0)), MoveType::or_opt(Some(0))],
+                None,
+                None,
+            );
+            let array_problem = Arc::new(Mutex::new(ArrayProblem::new(&move_type, &eval)));
+            let problem: Arc<Mutex<dyn Problem>> = array_problem.clone();
+            let termination = TerminationFunction::max_iterations(50);
+            let mut sim = TabuSearch::new(&problem, &termination, true, None);
+            let data = sim.run(false, None).unwrap();
+            assert_eq!(data.last().unwrap().1, 12.0);
+            let array_problem = array_problem.lock().unwrap();
+            let routes = array_problem
+                .routes(&array_problem.get_best_solution().unwrap())
+                .unwrap();
+            assert_eq!(routes.len(), 2);
+        }
+    }
+    #[test]
+    fn single_machine_test() {
+        let processing_times = vec![3.0, 2.0, 4.0];
+        let due_dates = vec![2.0, 6.0, 5.0];
+        let eval = Evaluation::single_machine(
+            processing_times.clone(),
+            due_dates.clone(),
+            Some(vec![1.0, 2.0, 3.0]),
+            None,
+        );
+        // Completion times 3, 5, 9.
+        assert_eq!(eval.eval(&[0, 1, 2]), 1.0 + 0.0 + 12.0);
+        let setup_times = vec![
+            vec![0.0, 1.0, 2.0],
+            vec![1.0, 0.0, 1.0],
+            vec![2.0, 1.0, 0.0],
+        ];
+        let eval = Evaluation::single_machine(processing_times, due_dates, None, Some(setup_times));
+        // Completion times 3, 6, 11.
+        assert_eq!(eval.eval(&[0, 1, 2]), 1.0 + 0.0 + 6.0);
+    }
+    #[test]
+    fn single_machine_delta_test() {
+        let processing_times = vec![3.0, 2.0, 4.0, 1.0, 5.0, 2.0, 3.0];
+        let due_dates = vec![4.0, 6.0, 12.0, 3.0, 20.0, 9.0, 30.0];
+        let weights = Some(vec![2.0, 1.0, 3.0, 1.0, 2.0, 4.0, 1.0]);
+        let setup_times: Vec<Vec<f64>> = (0..7)
+            .map(|i| (0..7).map(|j| ((i * 3 + j * 5) % 4) as f64).collect())
+            .collect();
+        let move_type = MoveType::multi_neighbor(
+            vec![
+                MoveType::swap(Some(0)),
+                MoveType::reverse(Some(0)),
+                MoveType::or_opt(Some(0)),
+            ],
+            None,
+            None,
+        );
+        for setup_times in [None, Some(setup_times)] {
+            let eval = Evaluation::single_machine(
+                processing_times.clone(),
+                due_dates.clone(),
+                weights.clone(),
+                setup_times,
+            );
+            let mut problem = ArrayProblem::new(&move_type, &eval);
+            let mut array: Vec<usize> = vec![3, 0, 6, 5, 4, 1, 2];
+            problem
+                .set_state(&State::Permutation(array.clone()))
+                .unwrap();
+            for (i, test_move) in problem.get_all_mov().iter().enumerate() {
+                let score_0 = eval.eval(&array);
+                let delta = eval.delta_eval(test_move, &mut array);
+                let cached = problem.delta_eval(test_move);
+                test_move.do_move(&mut array);
+                let score_1 = eval.eval(&array);
+                assert!((delta - (score_1 - score_0)).abs() < 1e-9);
+                assert!((cached - (score_1 - score_0)).abs() < 1e-9);
+                if i % 5 == 0 {
+                    problem.do_mov(test_move);
+                } else {
+                    test_move.undo_move(&mut array);
+                }
+            }
+        }
+    }
+    #[test]
+    fn flow_shop_test() {
+        let processing_times = vec![vec![3.0, 2.0], vec![1.0, 4.0], vec![2.0, 1.0]];
+        let order = [0, 1, 2];
+        let makespan = Evaluation::flow_shop(
+            processing_times.clone(),
+            FlowShopObjective::Makespan,
+            None,
+            None,
+        );
+        assert_eq!(makespan.eval(&order), 10.0);
+        let flow_time = Evaluation::flow_shop(
+            processing_times.clone(),
+            FlowShopObjective::TotalFlowTime,
+            None,
+            Some(vec![2.0, 1.0, 1.0]),
+        );
+        assert_eq!(flow_time.eval(&order), 29.0);
+        let tardiness = Evaluation::flow_shop(
+            processing_times,
+            FlowShopObjective::TotalTardiness,
+            Some(vec![4.0, 8.0, 12.0]),
+            None,
+        );
+        assert_eq!(tardiness.eval(&order), 2.0);
+    }
+    #[test]
+    fn flow_shop_delta_test() {
+        let processing_times = vec![
+            vec![3.0, 2.0, 5.0],
+            vec![1.0, 4.0, 2.0],
+            vec![2.0, 1.0, 6.0],
+            vec![4.0, 3.0, 1.0],
+            vec![2.0, 5.0, 3.0],
+            vec![6.0, 2.0, 2.0],
+        ];
+        let due_dates = Some(vec![10.0, 8.0, 20.0, 15.0, 12.0, 25.0]);
+        let weights = Some(vec![2.0, 1.0, 3.0, 1.0, 2.0, 1.0]);
+        let move_type = MoveType::multi_neighbor(
+            vec![MoveType::or_opt(Some(0)), MoveType::swap(Some(0))],
+            None,
+            None,
+        );
+        for objective in [
+            FlowShopObjective::Makespan,
+            FlowShopObjective::TotalFlowTime,
+            FlowShopObjective::TotalTardiness,
+        ] {
+            let eval = Evaluation::flow_shop(
+                processing_times.clone(),
+                objective,
+                due_dates.clone(),
+                weights.clone(),
+            );
+            let mut problem = ArrayProblem::new(&move_type, &eval);
+            let mut array: Vec<usize> = vec![3, 0, 5, 4, 1, 2];
+            problem
+                .set_state(&State::Permutation(array.clone()))
+                .unwrap();
+            for (i, test_move) in problem.get_all_mov().iter().enumerate() {
+                let score_0 = eval.eval(&array);
+                let delta = eval.delta_eval(test_move, &mut array);
+                let cached = problem.delta_eval(test_move);
+                test_move.do_move(&mut array);
+                let score_1 = eval.eval(&array);
+                assert!((delta - (score_1 - score_0)).abs() < 1e-9);
+                assert!((cached - (score_1 - score_0)).abs() < 1e-9);
+                if i % 4 == 0 {
+                    problem.do_mov(test_move);
+                } else {
+                    test_move.undo_move(&mut array);
+                }
+            }
+        }
+    }
+    #[test]
+    fn relocate_test() {
+        let distance_matrix: Vec<Vec<f64>> = vec![
+            vec![0.0, 2.0, 5.0, 8.0, 3.0],
+            vec![6.0, 0.0, 4.0, 1.0, 9.0],
+            vec![5.0, 1.0, 0.0, 7.0, 2.0],
+            vec![4.0, 1.0, 3.0, 0.0, 6.0],
+            vec![7.0, 8.0, 2.0, 5.0, 0.0],
+        ];
+        let flow_matrix = vec![
+            vec![0.0, 2.0, 0.0, 1.0, 3.0],
+            vec![2.0, 0.0, 4.0, 0.0, 5.0],
+            vec![0.0, 4.0, 0.0, 8.0, 1.0],
+            vec![1.0, 0.0, 8.0, 0.0, 6.0],
+            vec![3.0, 5.0, 1.0, 6.0, 0.0],
+        ];
+        let evals = [
+            Evaluation::tsp(distance_matrix.clone()),
+            Evaluation::Tsp {
+                distance_matrix: distance_matrix.clone(),
+                symmetric: false,
+            },
+            Evaluation::qap(distance_matrix, flow_matrix),
+        ];
+        let mut move_type = MoveType::or_opt(Some(0));
+        move_type.set_size(5);
+        for eval in evals {
+            let mut array: Vec<usize> = vec![3, 0, 4, 1, 2];
+            for test_move in move_type.get_all_mov() {
+                let score_0 = eval.eval(&array);
+                let delta = eval.delta_eval(&test_move, &mut array);
+                test_move.do_move(&mut array);
+                let score_1 = eval.eval(&array);
+                assert!((delta - (score_1 - score_0)).abs() < 1e-9);
+            }
+        }
+    }
+    #[test]
+    fn bins_delta_test() {
+        let weights = vec![2.0, 5.0, 4.0, 7.0, 1.0, 3.0, 8.0, 6.0, 2.0];
+        let evals = [
+            Evaluation::bins(weights.clone(), 10.0),
+            Evaluation::empty_space(weights.clone(), 10.0),
+            Evaluation::empty_space_exp(weights, 10.0),
+        ];
+        let move_type = MoveType::multi_neighbor(
+            vec![
+                MoveType::swap(Some(0)),
+                MoveType::reverse(Some(0)),
+                MoveType::or_opt(Some(0)),
+                MoveType::three_opt(Some(0)),
+                MoveType::block_exchange(3, Some(0)),
+            ],
+            None,
+            None,
+        );
+        for eval in evals {
+            let mut problem = ArrayProblem::new(&move_type, &eval);
+            let mut array: Vec<usize> = vec![3, 0, 8, 4, 1, 6, 2, 7, 5];
+            problem
+                .set_state(&State::Permutation(array.clone()))
+                .unwrap();
+            for test_move in problem.get_all_mov() {
+                let score_0 = eval.eval(&array);
+                let delta = eval.delta_eval(&test_move, &mut array);
+                let cached = problem.delta_eval(&test_move);
+                test_move.do_move(&mut array);
+                problem.do_mov(&test_move);
+                let score_1 = eval.eval(&array);
+                assert!((delta - (score_1 - score_0)).abs() < 1e-9);
+                assert!((cached - (score_1 - score_0)).abs() < 1e-9);
+            }
+        }
+    }
+    #[test]
+    fn asymmetric_reverse_test() {
+        let distance_matrix: Vec<Vec<f64>> = vec![
+            vec![0.0, 2.0, 5.0, 8.0, 3.0, 4.0],
+            vec![6.0, 0.0, 4.0, 1.0, 9.0, 2.0],
+            vec![5.0, 1.0, 0.0, 7.0, 2.0, 8.0],
+            vec![4.0, 1.0, 3.0, 0.0, 6.0, 5.0],
+            vec![7.0, 8.0, 2.0, 5.0, 0.0, 1.0],
+            vec![3.0, 6.0, 9.0, 2.0, 4.0, 0.0],
+        ];
+        let eval = Evaluation::tsp(distance_matrix);
+        let move_type = MoveType::multi_neighbor(
+            vec![
+                MoveType::reverse(Some(0)),
+                MoveType::three_opt(Some(0)),
+                MoveType::or_opt(Some(0)),
+            ],
+            None,
+            None,
+        );
+        let mut problem = ArrayProblem::new(&move_type, &eval);
+        let mut array: Vec<usize> = vec![3, 0, 5, 4, 1, 2];
+        problem
+            .set_state(&State::Permutation(array.clone()))
+            .unwrap();
+        for (i, test_move) in problem.get_all_mov().iter().enumerate() {
+            let score_0 = eval.eval(&array);
+            let delta = eval.delta_eval(test_move, &mut array);
+            let cached = problem.delta_eval(test_move);
+            test_move.do_move(&mut array);
+            problem.do_mov(test_move);
+            let score_1 = eval.eval(&array);
+            assert!((delta - (score_1 - score_0)).abs() < 1e-9);
+            assert!((cached - (score_1 - score_0)).abs() < 1e-9);
+            if i % 3 == 0 {
+                test_move.undo_move(&mut array);
+                problem.undo_mov(test_move);
+            }
+        }
+    }
+    #[test]
+    fn tsp_path_test() {
+        let positions: [f64; 5] = [0.0, 1.0, 2.0, 3.0, 4.0];
+        let distance_matrix: Vec<Vec<f64>> = positions
+            .iter()
+            .map(|a| positions.iter().map(|b| (a - b).abs()).collect())
+            .collect();
+        let eval = Evaluation::tsp_path(distance_matrix.clone(), None, None);
+        assert_eq!(eval.eval(&[0, 1, 2, 3, 4]), 4.0);
+        assert_eq!(eval.eval(&[4, 0, 1, 2, 3]), 7.0);
+        let eval = Evaluation::tsp_path(distance_matrix, Some(2), Some(0));
+        let mut order = vec![0, 1, 2, 3, 4];
+        eval.place_fixed_ends(&mut order);
+        assert_eq!(order, [2, 1, 4, 3, 0]);
+        assert!(eval.check_fixed_ends(&order).is_ok());
+        assert!(eval.check_fixed_ends(&[0, 1, 2, 3, 4]).is_err());
+        assert!(!eval.fixed_ends_kept_by(&MoveType::tsp(None)));
+
+        let move_type = MoveType::fixed_ends(MoveType::reverse(Some(0)), true, true);
+        let problem: Arc<Mutex<dyn Problem>> =
+            Arc::new(Mutex::new(ArrayProblem::new(&move_type, &eval)));
+        let termination = TerminationFunction::max_iterations(20);
+        let mut sim = TabuSearch::new(&problem, &termination, true, None);
+        let data = sim.run(false, None).unwrap();
+        assert_eq!(data.last().unwrap().1, 6.0);
+        let problem = problem.lock().unwrap();
+        let best = problem.get_best_solution().unwrap();
+        let best = best.permutation(5).unwrap();
+        assert_eq!((best[0], best[4]), (2, 0));
+    }
+    #[test]
+    fn tsp_path_delta_test() {
+        let distance_matrix: Vec<Vec<f64>> = vec![
+            vec![0.0, 2.0, 5.0, 8.0, 3.0, 4.0],
+            vec![6.0, 0.0, 4.0, 1.0, 9.0, 2.0],
+            vec![5.0, 1.0, 0.0, 7.0, 2.0, 8.0],
+            vec![4.0, 1.0, 3.0, 0.0, 6.0, 5.0],
+            vec![7.0, 8.0, 2.0, 5.0, 0.0, 1.0],
+            vec![3.0, 6.0, 9.0, 2.0, 4.0, 0.0],
+        ];
+        let neighborhoods = || {
+            vec![
+                MoveType::swap(Some(0)),
+                MoveType::reverse(Some(0)),
+                MoveType::three_opt(Some(0)),
+                MoveType::or_opt(Some(0)),
+                MoveType::block_exchange(2, Some(0)),
+            ]
+        };
+        let fixed = neighborhoods()
+            .into_iter()
+            .map(|move_type| MoveType::fixed_ends(move_type, true, true))
+            .collect();
+        let cases = [
+            (
+                Evaluation::tsp(distance_matrix.clone()),
+                MoveType::multi_neighbor(neighborhoods(), None, None),
+            ),
+            (
+                Evaluation::tsp_path(distance_matrix.clone(), None, None),
+                MoveType::multi_neighbor(neighborhoods(), None, None),
+            ),
+            (
+                Evaluation::tsp_path(distance_matrix, Some(3), Some(2)),
+                MoveType::multi_neighbor(fixed, None, None),
+            ),
+        ];
+        for (eval, move_type) in cases {
+            let mut problem = ArrayProblem::new(&move_type, &eval);
+            let mut array: Vec<usize> = vec![3, 0, 5, 4, 1, 2];
+            problem
+                .set_state(&State::Permutation(array.clone()))
+                .unwrap();
+            for (i, test_move) in problem.get_all_mov().iter().enumerate() {
+                let score_0 = eval.eval(&array);
+                let delta = eval.delta_eval(test_move, &mut array);
+                let cached = problem.delta_eval(test_move);
+                test_move.do_move(&mut array);
+                problem.do_mov(test_move);
+                let score_1 = eval.eval(&array);
+                assert!((delta - (score_1 - score_0)).abs() < 1e-9);
+                assert!((cached - (score_1 - score_0)).abs() < 1e-9);
+                if i % 3 == 0 {
+                    test_move.undo_move(&mut array);
+                    problem.undo_mov(test_move);
+                }
+            }
+            assert!(eval.check_fixed_ends(&array).is_ok());
+        }
+    }
+    #[test]
+    fn linear_ordering_test() {
+        let weight_matrix = vec![
+            vec![0.0, 2.0, 5.0, 8.0, 3.0],
+            vec![6.0, 0.0, 4.0, 1.0, 9.0],
+            vec![5.0, 1.0, 0.0, 7.0, 2.0],
+            vec![4.0, 1.0, 3.0, 0.0, 6.0],
+            vec![7.0, 8.0, 2.0, 5.0, 0.0],
+        ];
+        let eval = Evaluation::linear_ordering(weight_matrix);
+        assert_eq!(eval.eval(&[0, 1, 2]), 2.0 + 5.0 + 4.0);
+        assert_eq!(eval.eval(&[2, 1, 0]), 1.0 + 5.0 + 6.0);
+        let move_type = MoveType::multi_neighbor(
+            vec![
+                MoveType::swap(Some(0)),
+                MoveType::reverse(Some(0)),
+                MoveType::or_opt(Some(0)),
+                MoveType::three_opt(Some(0)),
+            ],
+            None,
+            None,
+        );
+        let mut problem = ArrayProblem::new(&move_type, &eval);
+        let mut array: Vec<usize> = vec![3, 0, 4, 1, 2];
+        for test_move in problem.get_all_mov() {
+            let score_0 = eval.eval(&array);
+            let delta = eval.delta_eval(&test_move, &mut array);
+            test_move.do_move(&mut array);
+            let score_1 = eval.eval(&array);
+            assert!((delta - (score_1 - score_0)).abs() < 1e-9);
+        }
+    }
+    #[test]
+    fn linear_arrangement_test() {
+        let edges = vec![
+            (0, 1),
+            (1, 2),
+            (2, 3),
+            (3, 4),
+            (4, 5),
+            (0, 5),
+            (1, 4),
+            (2, 2),
+        ];
+        let sum = Evaluation::linear_arrangement(6, edges.clone(), ArrangementObjective::Sum, None);
+        let bandwidth = Evaluation::linear_arrangement(
+            6,
+            edges,
+            ArrangementObjective::Bandwidth,
+            Some(vec![1.0, 1.0, 1.0, 1.0, 1.0, 0.5, 2.0, 1.0]),
+        );
+        assert_eq!(sum.eval(&[0, 1, 2, 3, 4, 5]), 5.0 + 5.0 + 3.0);
+        assert_eq!(bandwidth.eval(&[0, 1, 2, 3, 4, 5]), 6.0);
+        assert_eq!(
+            sum.eval(&[2, 1, 0, 3, 4, 5]),
+            1.0 + 1.0 + 3.0 + 1.0 + 1.0 + 3.0 + 3.0
+        );
+        let move_type = MoveType::multi_neighbor(
+            vec![
+                MoveType::swap(Some(0)),
+                MoveType::reverse(Some(0)),
+                MoveType::or_opt(Some(0)),
+                MoveType::block_exchange(2, Some(0)),
+            ],
+            None,
+            None,
+        );
+        for eval in [sum, bandwidth] {
+            let mut problem = ArrayProblem::new(&move_type, &eval);
+            let mut array: Vec<usize> = vec![3, 0, 5, 4, 1, 2];
+            problem
+                .set_state(&State::Permutation(array.clone()))
+                .unwrap();
+            for (i, test_move) in problem.get_all_mov().iter().enumerate() {
+                let score_0 = eval.eval(&array);
+                let delta = eval.delta_eval(test_move, &mut array);
+                let cached = problem.delta_eval(test_move);
+                test_move.do_move(&mut array);
+                problem.do_mov(test_move);
+                let score_1 = eval.eval(&array);
+                assert!((delta - (score_1 - score_0)).abs() < 1e-9);
+                assert!((cached - (score_1 - score_0)).abs() < 1e-9);
+                if i % 3 == 0 {
+                    test_move.undo_move(&mut array);
+                    problem.undo_mov(test_move);
+                }
+            }
+        }
+    }
+    #[test]
+    fn three_opt_test() {
+        let distance_matrix: Vec<Vec<f64>> = vec![
+            vec![0.0, 2.0, 5.0, 8.0, 3.0, 4.0],
+            vec![6.0, 0.0, 4.0, 1.0, 9.0, 2.0],
+            vec![5.0, 1.0, 0.0, 7.0, 2.0, 8.0],
+            vec![4.0, 1.0, 3.0, 0.0, 6.0, 5.0],
+            vec![7.0, 8.0, 2.0, 5.0, 0.0, 1.0],
+            vec![3.0, 6.0, 9.0, 2.0, 4.0, 0.0],
+        ];
+        let evals = [
+            Evaluation::tsp(distance_matrix.clone()),
+            Evaluation::Tsp {
+                distance_matrix,
+                symmetric: false,
+            },
+        ];
+        let mut three_opt = MoveType::three_opt(Some(0));
+        three_opt.set_size(6);
+        let mut block_exchange = MoveType::block_exchange(2, Some(0));
+        block_exchange.set_size(6);
+        let moves = [three_opt.get_all_mov(), block_exchange.get_all_mov()].concat();
+        for eval in evals {
+            let mut array: Vec<usize> = vec![3, 0, 5, 4, 1, 2];
+            for test_move in &moves {
+                let score_0 = eval.eval(&array);
+                let delta = eval.delta_eval(test_move, &mut array);
+                test_move.do_move(&mut array);
+                let score_1 = eval.eval(&array);
+                assert!((delta - (score_1 - score_0)).abs() < 1e-9);
+            }
+        }
+    }
+    #[test]
+    fn nearest_neighbors_test() {
+        let eval = Evaluation::tsp(vec![
+            vec![0.0, 2.0, 5.0, 8.0],
+            vec![2.0, 0.0, 4.0, 1.0],
+            vec![5.0, 4.0, 0.0, 7.0],
+            vec![8.0, 1.0, 7.0, 0.0],
+        ]);
+        assert_eq!(
+            eval.nearest_neighbors(2),
+            Some(vec![vec![1, 2], vec![3, 0], vec![1, 0], vec![1, 2]])
+        );
+        assert_eq!(
+            Evaluation::bins(vec![1.0, 2.0], 3.0).nearest_neighbors(2),
+            None
+        );
+    }
+    #[test]
+    fn custom_test() {
+        let tsp = Evaluation::tsp(vec![
+            vec![0.0, 2.0, 5.0, 8.0],
+            vec![2.0, 0.0, 4.0, 1.0],
+            vec![5.0, 4.0, 0.0, 7.0],
+            vec![8.0, 1.0, 7.0, 0.0],
+        ]);
+        let inner = tsp.clone();
+        let eval = Evaluation::custom(4, Arc::new(move |order: &[usize]| inner.eval(order)), None);
+        let inner = tsp.clone();
+        let with_delta = Evaluation::custom(
+            4,
+            Arc::new(|_: &[usize]| 0.0),
+            Some(Arc::new(move |order: &[usize], mov: &Move| {
+                inner.delta_eval(mov, &mut order.to_vec())
+            })),
+        );
+        assert_eq!(eval.length(), 4);
+        let tests = vec![Move::Swap(1, 2), Move::Reverse(0, 2), Move::Swap(0, 3)];
+        let mut array: Vec<usize> = (0..4).collect();
+        for test_move in tests {
+            let delta = tsp.delta_eval(&test_move, &mut array);
+            assert_eq!(eval.eval(&array), tsp.eval(&array));
+            assert_eq!(eval.delta_eval(&test_move, &mut array), delta);
+            assert_eq!(with_delta.delta_eval(&test_move, &mut array), delta);
+            test_move.do_move(&mut array);
+        }
+    }
 }
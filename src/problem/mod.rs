@@ -1,8 +1,27 @@
 pub mod array_problem;
+pub mod assignment_evaluation;
+pub mod assignment_problem;
+pub mod binary_evaluation;
+pub mod binary_problem;
+pub mod construction;
+mod delta_cache;
 pub mod evaluation;
 pub mod r#move;
 pub mod problem;
+pub mod real_evaluation;
+pub mod real_vector_problem;
+pub mod state;
 pub use self::array_problem::ArrayProblem;
-pub use self::evaluation::Evaluation;
-pub use self::problem::Problem;
-pub use self::r#move::MoveType;
+pub use self::assignment_evaluation::AssignmentEvaluation;
+pub use self::assignment_problem::AssignmentProblem;
+pub use self::binary_evaluation::BinaryEvaluation;
+pub use self::binary_problem::BinaryProblem;
+pub use self::construction::Construction;
+pub use self::evaluation::{
+    ArrangementObjective, DeltaFn, EvalFn, Evaluation, FlowShopObjective, TimeWindowReport, Visit,
+};
+pub use self::problem::{ElementProblem, Problem};
+pub use self::r#move::{CustomMove, Move, MoveType, Neighborhood, RealMoveType, Reconnection};
+pub use self::real_evaluation::RealEvaluation;
+pub use self::real_vector_problem::RealVectorProblem;
+pub use self::state::State;
@@ -1,6 +1,283 @@
+use std::fmt;
+use std::sync::Arc;
 use std::usize;
 
-use rand::{rngs::SmallRng, Rng, SeedableRng};
+use pyo3::prelude::*;
+use rand::{rngs::SmallRng, seq::index::sample, Rng, SeedableRng};
+
+/// A single move of a neighborhood. Every variant carries all the data needed to perform it,
+/// so a move can be applied without knowing which [MoveType] produced it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Move {
+    /// Swaps the elements on both indices.
+    Swap(usize, usize),
+    /// Reverses the elements between both indices, bounds included.
+    Reverse(usize, usize),
+    /// Moves the segment of the given length starting at the first index, so it starts at the
+    /// second index once the segment is put back.
+    Relocate(usize, usize, usize),
+    /// Reconnects the segments `i..=j` and `j + 1..=k`, given as `(i, j, k, reconnection)`.
+    ThreeOpt(usize, usize, usize, Reconnection),
+    /// Exchanges the blocks of the given length starting at both indices, the first block
+    /// ends before the second one starts.
+    BlockExchange(usize, usize, usize),
+    /// Flips the bit on the given index of a binary state.
+    Flip(usize),
+    /// Flips the bits on both indices of a binary state.
+    FlipPair(usize, usize),
+    /// Gives the position on the first index the value of the second one in an assignment.
+    Assign(usize, usize),
+    /// Adds the step to the coordinate on the index of a real vector.
+    Perturb(usize, f64),
+    /// Move of a user-defined [Neighborhood].
+    Custom(CustomMove),
+}
+impl Move {
+    /// Performs the move on the given array.
+    pub(crate) fn do_move<T>(&self, array: &mut [T]) {
+        match self {
+            Move::Swap(i, j) => array.swap(*i, *j),
+            Move::Reverse(i, j) => array[*i..=*j].reverse(),
+            Move::Relocate(from, to, len) => {
+                if from < to {
+                    array[*from..*to + *len].rotate_left(*len);
+                } else {
+                    array[*to..*from + *len].rotate_right(*len);
+                }
+            }
+            Move::ThreeOpt(i, j, k, reconnection) => {
+                let (first, second) = (*j + 1 - *i, *k - *j);
+                let segments = &mut array[*i..=*k];
+                match reconnection {
+                    Reconnection::ReverseBoth => {
+                        segments[..first].reverse();
+                        segments[first..].reverse();
+                    }
+                    Reconnection::Exchange => segments.rotate_left(first),
+                    Reconnection::ExchangeReverseSecond => {
+                        segments.reverse();
+                        segments[second..].reverse();
+                    }
+                    Reconnection::ExchangeReverseFirst => {
+                        segments.reverse();
+                        segments[..second].reverse();
+                    }
+                }
+            }
+            Move::BlockExchange(i, j, len) => {
+                let (first, second) = array.split_at_mut(*j);
+                first[*i..*i + *len].swap_with_slice(&mut second[..*len]);
+            }
+            Move::Flip(..) | Move::FlipPair(..) => {
+                panic!("Flip moves can only be done on a binary state")
+            }
+            Move::Assign(..) => panic!("Assign moves can only be done on an assignment"),
+            Move::Perturb(..) => panic!("Perturb moves can only be done on a real vector"),
+            Move::Custom(..) => panic!("Custom moves can only be done by the problem"),
+        }
+    }
+
+    /// Reverts the move on the given array.
+    pub(crate) fn undo_move<T>(&self, array: &mut [T]) {
+        match self {
+            Move::Swap(..)
+            | Move::Reverse(..)
+            | Move::BlockExchange(..)
+            | Move::Flip(..)
+            | Move::FlipPair(..) => self.do_move(array),
+            Move::ThreeOpt(i, j, k, reconnection) => {
+                // After an exchange the second segment comes first.
+                let split = *i + *k - *j - 1;
+                match reconnection {
+                    Reconnection::ReverseBoth => self.do_move(array),
+                    Reconnection::Exchange => {
+                        Move::ThreeOpt(*i, split, *k, Reconnection::Exchange).do_move(array)
+                    }
+                    Reconnection::ExchangeReverseSecond => {
+                        Move::ThreeOpt(*i, split, *k, Reconnection::ExchangeReverseFirst)
+                            .do_move(array)
+                    }
+                    Reconnection::ExchangeReverseFirst => {
+                        Move::ThreeOpt(*i, split, *k, Reconnection::ExchangeReverseSecond)
+                            .do_move(array)
+                    }
+                }
+            }
+            Move::Relocate(from, to, len) => Move::Relocate(*to, *from, *len).do_move(array),
+            Move::Assign(..) | Move::Perturb(..) | Move::Custom(..) => {
+                panic!("Assign, perturb and custom moves can only be undone by the problem")
+            }
+        }
+    }
+
+    /// Elements next to the edges the move breaks or makes, taken from the order before the
+    /// move. Empty for moves that don't work on a cyclic order.
+    pub(crate) fn endpoints(&self, order: &[usize]) -> Vec<usize> {
+        let len = order.len();
+        let around = |i: usize| [order[(i + len - 1) % len], order[i], order[(i + 1) % len]];
+        match *self {
+            Move::Swap(i, j) => [around(i), around(j)].concat(),
+            Move::Reverse(i, j) => vec![
+                order[(i + len - 1) % len],
+                order[i],
+                order[j],
+                order[(j + 1) % len],
+            ],
+            Move::Relocate(from, to, segment) => {
+                let rest = |i: usize| {
+                    let i = i % (len - segment);
+                    if i < from {
+                        order[i]
+                    } else {
+                        order[i + segment]
+                    }
+                };
+                vec![
+                    order[(from + len - 1) % len],
+                    order[from],
+                    order[from + segment - 1],
+                    order[(from + segment) % len],
+                    rest(to + len - segment - 1),
+                    rest(to),
+                ]
+            }
+            Move::ThreeOpt(i, j, k, _) => vec![
+                order[i - 1],
+                order[i],
+                order[j],
+                order[j + 1],
+                order[k],
+                order[(k + 1) % len],
+            ],
+            Move::BlockExchange(i, j, segment) => vec![
+                order[i - 1],
+                order[i],
+                order[i + segment - 1],
+                order[i + segment],
+                order[j - 1],
+                order[j],
+                order[j + segment - 1],
+                order[(j + segment) % len],
+            ],
+            _ => vec![],
+        }
+    }
+
+    /// The same move on the positions `offset` further along.
+    pub(crate) fn shifted(&self, offset: usize) -> Move {
+        match *self {
+            Move::Swap(i, j) => Move::Swap(i + offset, j + offset),
+            Move::Reverse(i, j) => Move::Reverse(i + offset, j + offset),
+            Move::Relocate(from, to, len) => Move::Relocate(from + offset, to + offset, len),
+            Move::ThreeOpt(i, j, k, reconnection) => {
+                Move::ThreeOpt(i + offset, j + offset, k + offset, reconnection)
+            }
+            Move::BlockExchange(i, j, len) => Move::BlockExchange(i + offset, j + offset, len),
+            _ => panic!("Only moves of a permutation can be shifted"),
+        }
+    }
+
+    /// First and last position the move changes, `None` if unknown.
+    pub(crate) fn changed_range(&self) -> Option<(usize, usize)> {
+        match *self {
+            Move::Swap(i, j) => Some((i.min(j), i.max(j))),
+            Move::Reverse(i, j) | Move::ThreeOpt(i, _, j, _) => Some((i, j)),
+            Move::Relocate(from, to, len) => Some((from.min(to), from.max(to) + len - 1)),
+            Move::BlockExchange(i, j, len) => Some((i, j + len - 1)),
+            _ => None,
+        }
+    }
+}
+
+/// Moves are given to Python as a tuple of their name followed by their values,
+/// e.g. `("swap", 1, 2)`, custom moves as the list returned by their neighborhood.
+impl IntoPy<PyObject> for Move {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        match self {
+            Move::Swap(i, j) => ("swap", i, j).into_py(py),
+            Move::Reverse(i, j) => ("reverse", i, j).into_py(py),
+            Move::Relocate(from, to, len) => ("relocate", from, to, len).into_py(py),
+            Move::ThreeOpt(i, j, k, reconnection) => {
+                ("three_opt", i, j, k, reconnection.name()).into_py(py)
+            }
+            Move::BlockExchange(i, j, len) => ("block_exchange", i, j, len).into_py(py),
+            Move::Flip(i) => ("flip", i).into_py(py),
+            Move::FlipPair(i, j) => ("flip_pair", i, j).into_py(py),
+            Move::Assign(i, value) => ("assign", i, value).into_py(py),
+            Move::Perturb(i, step) => ("perturb", i, step).into_py(py),
+            Move::Custom(custom) => custom.values.into_py(py),
+        }
+    }
+}
+
+/// How a 3-opt move puts the segments `B = i..=j` and `C = j + 1..=k` back together, the
+/// removed edges are always the ones entering `B`, between `B` and `C`, and leaving `C`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Reconnection {
+    /// `B' C'`, both segments reversed in place.
+    ReverseBoth,
+    /// `C B`, the segments exchanged without reversing them (or-3opt).
+    Exchange,
+    /// `C' B`, the segments exchanged with the second one reversed.
+    ExchangeReverseSecond,
+    /// `C B'`, the segments exchanged with the first one reversed.
+    ExchangeReverseFirst,
+}
+impl Reconnection {
+    /// All reconnections replacing the three removed edges by three new ones.
+    pub const ALL: [Reconnection; 4] = [
+        Reconnection::ReverseBoth,
+        Reconnection::Exchange,
+        Reconnection::ExchangeReverseSecond,
+        Reconnection::ExchangeReverseFirst,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Reconnection::ReverseBoth => "reverse_both",
+            Reconnection::Exchange => "exchange",
+            Reconnection::ExchangeReverseSecond => "exchange_reverse_second",
+            Reconnection::ExchangeReverseFirst => "exchange_reverse_first",
+        }
+    }
+}
+
+/// Neighborhood defined outside the crate, used to wrap Python objects. Its moves are lists
+/// of integers that only the neighborhood itself knows how to perform.
+pub trait Neighborhood: Send + Sync {
+    /// Returns a random move.
+    fn get_mov(&self) -> Vec<usize>;
+    /// Returns all moves.
+    fn get_all_mov(&self) -> Vec<Vec<usize>>;
+    /// Performs the move on the state.
+    fn do_move(&self, state: &mut [usize], mov: &[usize]);
+    /// Seeds the neighborhood, does nothing by default.
+    fn set_seed(&self, _seed: u64) {}
+}
+
+/// A move of a [Neighborhood] together with the neighborhood that performs it.
+#[derive(Clone)]
+pub struct CustomMove {
+    pub values: Vec<usize>,
+    neighborhood: Arc<dyn Neighborhood>,
+}
+impl CustomMove {
+    /// Performs the move on the given state.
+    pub(crate) fn do_move(&self, state: &mut [usize]) {
+        self.neighborhood.do_move(state, &self.values);
+    }
+}
+impl PartialEq for CustomMove {
+    fn eq(&self, other: &Self) -> bool {
+        self.values == other.values && Arc::ptr_eq(&self.neighborhood, &other.neighborhood)
+    }
+}
+impl fmt::Debug for CustomMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CustomMove").field(&self.values).finish()
+    }
+}
+
 #[derive(Clone)]
 pub enum MoveType {
     Reverse {
@@ -15,84 +292,180 @@ pub enum MoveType {
         rng: Box<SmallRng>,
         size: usize,
     },
+    Relocate {
+        rng: Box<SmallRng>,
+        size: usize,
+        max_len: usize,
+    },
+    ThreeOpt {
+        rng: Box<SmallRng>,
+        size: usize,
+        reconnections: Vec<Reconnection>,
+    },
+    BlockExchange {
+        rng: Box<SmallRng>,
+        size: usize,
+        max_len: usize,
+    },
+    Flip {
+        rng: Box<SmallRng>,
+        size: usize,
+    },
+    FlipPair {
+        rng: Box<SmallRng>,
+        size: usize,
+    },
+    Assign {
+        rng: Box<SmallRng>,
+        size: usize,
+        domain: usize,
+    },
     MultiNeighbor {
+        rng: Box<SmallRng>,
         move_types: Vec<MoveType>,
         weights: Vec<f64>,
     },
+    /// Moves of the inner neighborhood that keep the first `start` and last `end` positions
+    /// in place.
+    FixedEnds {
+        move_type: Box<MoveType>,
+        start: usize,
+        end: usize,
+    },
+    Custom {
+        neighborhood: Arc<dyn Neighborhood>,
+    },
 }
 impl MoveType {
     pub fn reverse(seed: Option<u64>) -> MoveType {
-        let rng = if seed.is_some() {
-            SmallRng::seed_from_u64(seed.unwrap())
-        } else {
-            SmallRng::from_entropy()
-        };
         MoveType::Reverse {
-            rng: Box::new(rng),
+            rng: Box::new(rng_from_seed(seed)),
             size: 0,
         }
     }
     pub fn swap(seed: Option<u64>) -> MoveType {
-        let rng = if seed.is_some() {
-            SmallRng::seed_from_u64(seed.unwrap())
-        } else {
-            SmallRng::from_entropy()
-        };
         MoveType::Swap {
-            rng: Box::new(rng),
+            rng: Box::new(rng_from_seed(seed)),
             size: 0,
         }
     }
     pub fn tsp(seed: Option<u64>) -> MoveType {
-        let rng = if seed.is_some() {
-            SmallRng::seed_from_u64(seed.unwrap())
-        } else {
-            SmallRng::from_entropy()
-        };
         MoveType::Tsp {
-            rng: Box::new(rng),
+            rng: Box::new(rng_from_seed(seed)),
+            size: 0,
+        }
+    }
+    /// Removes a single element and inserts it elsewhere.
+    pub fn relocate(seed: Option<u64>) -> MoveType {
+        MoveType::Relocate {
+            rng: Box::new(rng_from_seed(seed)),
+            size: 0,
+            max_len: 1,
+        }
+    }
+    /// Moves a segment of 1 to 3 elements elsewhere, keeping its order.
+    pub fn or_opt(seed: Option<u64>) -> MoveType {
+        MoveType::Relocate {
+            rng: Box::new(rng_from_seed(seed)),
+            size: 0,
+            max_len: 3,
+        }
+    }
+    /// Removes three edges and reconnects the segments in every way that adds three new edges.
+    pub fn three_opt(seed: Option<u64>) -> MoveType {
+        MoveType::ThreeOpt {
+            rng: Box::new(rng_from_seed(seed)),
+            size: 0,
+            reconnections: Reconnection::ALL.to_vec(),
+        }
+    }
+    /// Exchanges two adjacent segments without reversing them.
+    pub fn or_3opt(seed: Option<u64>) -> MoveType {
+        MoveType::ThreeOpt {
+            rng: Box::new(rng_from_seed(seed)),
+            size: 0,
+            reconnections: vec![Reconnection::Exchange],
+        }
+    }
+    /// Exchanges two blocks of the same length, at most `max_len` long.
+    pub fn block_exchange(max_len: usize, seed: Option<u64>) -> MoveType {
+        assert!(max_len > 0, "Blocks need at least one element");
+        MoveType::BlockExchange {
+            rng: Box::new(rng_from_seed(seed)),
             size: 0,
+            max_len,
         }
     }
-    pub fn multi_neighbor(move_types: Vec<MoveType>, weights: Option<Vec<f64>>) -> MoveType {
+    pub fn flip(seed: Option<u64>) -> MoveType {
+        MoveType::Flip {
+            rng: Box::new(rng_from_seed(seed)),
+            size: 0,
+        }
+    }
+    pub fn flip_pair(seed: Option<u64>) -> MoveType {
+        MoveType::FlipPair {
+            rng: Box::new(rng_from_seed(seed)),
+            size: 0,
+        }
+    }
+    pub fn assign(seed: Option<u64>) -> MoveType {
+        MoveType::Assign {
+            rng: Box::new(rng_from_seed(seed)),
+            size: 0,
+            domain: 0,
+        }
+    }
+    /// Combines several neighborhoods, random moves come from a neighborhood drawn according
+    /// to the weights, equal weights by default.
+    pub fn multi_neighbor(
+        move_types: Vec<MoveType>,
+        weights: Option<Vec<f64>>,
+        seed: Option<u64>,
+    ) -> MoveType {
         let len = move_types.len();
+        let weights = weights.unwrap_or(vec![1.0 / len as f64; len]);
+        assert_eq!(weights.len(), len, "Every neighborhood needs a weight");
         MoveType::MultiNeighbor {
+            rng: Box::new(rng_from_seed(seed)),
             move_types,
-            weights: weights.unwrap_or(vec![1.0 / len as f64; len]),
+            weights,
         }
     }
-
-    pub(crate) fn do_move(&self, array: &mut Vec<usize>, indices: (usize, usize)) {
-        match self {
-            MoveType::Reverse { rng: _, size: _ } => {
-                for i in 0..(indices.1 - indices.0 + 1) / 2 {
-                    array.swap(indices.0 + i, indices.1 - i);
-                }
-            }
-            MoveType::Swap { rng: _, size: _ } | MoveType::Tsp { rng: _, size: _ } => {
-                array.swap(indices.0, indices.1);
-            }
-            MoveType::MultiNeighbor {
-                move_types: _,
-                weights: _,
-            } => {
-                panic!("MultiNeighbor doesn't support do_move")
-            }
+    /// Keeps the first and/or last position of a permutation in place, e.g. the fixed start
+    /// and end of an open TSP path. [tsp](MoveType::tsp) equals a fixed start with swaps.
+    pub fn fixed_ends(move_type: MoveType, start: bool, end: bool) -> MoveType {
+        assert!(
+            matches!(
+                move_type,
+                MoveType::Reverse { .. }
+                    | MoveType::Swap { .. }
+                    | MoveType::Tsp { .. }
+                    | MoveType::Relocate { .. }
+                    | MoveType::ThreeOpt { .. }
+                    | MoveType::BlockExchange { .. }
+            ),
+            "Only permutation neighborhoods can keep their ends in place"
+        );
+        MoveType::FixedEnds {
+            move_type: Box::new(move_type),
+            start: usize::from(start),
+            end: usize::from(end),
         }
     }
 
-    pub(crate) fn get_mov(&mut self) -> (usize, usize) {
+    pub fn custom(neighborhood: Arc<dyn Neighborhood>) -> MoveType {
+        MoveType::Custom { neighborhood }
+    }
+
+    pub(crate) fn get_mov(&mut self) -> Move {
         match self {
-            MoveType::Reverse { rng, size } | MoveType::Swap { rng, size } => {
-                let i = rng.gen_range(0..*size);
-                let mut j = rng.gen_range(1..*size);
-                while i == j {
-                    j = rng.gen_range(1..*size);
-                }
-                if j < i {
-                    return (j, i);
-                }
-                (i, j)
+            MoveType::Reverse { rng, size } => {
+                let (i, j) = random_pair(rng, *size);
+                Move::Reverse(i, j)
+            }
+            MoveType::Swap { rng, size } => {
+                let (i, j) = random_pair(rng, *size);
+                Move::Swap(i, j)
             }
             MoveType::Tsp { rng, size } => {
                 let i = rng.gen_range(1..*size);
@@ -101,63 +474,357 @@ impl MoveType {
                     j = rng.gen_range(1..*size);
                 }
                 if j < i {
-                    return (j, i);
+                    return Move::Swap(j, i);
+                }
+                Move::Swap(i, j)
+            }
+            MoveType::Relocate { rng, size, max_len } => {
+                assert!(*size >= 2, "Relocate moves need at least 2 elements");
+                let len = rng.gen_range(1..=(*max_len).min(*size - 1));
+                let from = rng.gen_range(0..=*size - len);
+                let mut to = rng.gen_range(0..*size - len);
+                if to >= from {
+                    to += 1;
                 }
-                (i, j)
+                Move::Relocate(from, to, len)
+            }
+            MoveType::ThreeOpt {
+                rng,
+                size,
+                reconnections,
+            } => {
+                assert!(*size >= 3, "3-opt moves need at least 3 elements");
+                // The segments never contain the first element, so the edge entering the
+                // first segment and the one leaving the second are always different.
+                let mut cuts = sample(rng.as_mut(), *size, 3).into_vec();
+                cuts.sort_unstable();
+                let reconnection = reconnections[rng.gen_range(0..reconnections.len())];
+                Move::ThreeOpt(cuts[0] + 1, cuts[1], cuts[2], reconnection)
+            }
+            MoveType::BlockExchange { rng, size, max_len } => {
+                assert!(*size >= 3, "Block exchange moves need at least 3 elements");
+                let len = rng.gen_range(1..=(*max_len).min((*size - 1) / 2));
+                let i = rng.gen_range(1..=*size - 2 * len);
+                let j = rng.gen_range(i + len..=*size - len);
+                Move::BlockExchange(i, j, len)
+            }
+            MoveType::Flip { rng, size } => Move::Flip(rng.gen_range(0..*size)),
+            MoveType::FlipPair { rng, size } => {
+                let (i, j) = random_pair(rng, *size);
+                Move::FlipPair(i, j)
+            }
+            MoveType::Assign { rng, size, domain } => {
+                Move::Assign(rng.gen_range(0..*size), rng.gen_range(0..*domain))
             }
             MoveType::MultiNeighbor {
-                move_types: _,
-                weights: _,
+                rng,
+                move_types,
+                weights,
             } => {
-                panic!("MultiNeighbor doesn't support get_move");
+                let mut target = rng.gen::<f64>() * weights.iter().sum::<f64>();
+                let mut index = move_types.len() - 1;
+                for (i, weight) in weights.iter().enumerate() {
+                    if target < *weight {
+                        index = i;
+                        break;
+                    }
+                    target -= weight;
+                }
+                move_types[index].get_mov()
             }
+            MoveType::FixedEnds {
+                move_type, start, ..
+            } => move_type.get_mov().shifted(*start),
+            MoveType::Custom { neighborhood } => Move::Custom(CustomMove {
+                values: neighborhood.get_mov(),
+                neighborhood: neighborhood.clone(),
+            }),
         }
     }
 
-    pub(crate) fn get_all_mov(&self) -> Vec<(usize, usize)> {
+    pub(crate) fn get_all_mov(&self) -> Vec<Move> {
+        MoveIter::new(self.enumeration(), None).collect()
+    }
+
+    /// Lazily gives the moves of [get_all_mov](MoveType::get_all_mov), in a random order drawn
+    /// from the neighborhood's generator if `shuffle`. Custom neighborhoods keep their order.
+    pub(crate) fn iter_mov(&mut self, shuffle: bool) -> MoveIter {
+        let moves = self.enumeration();
+        MoveIter::new(moves, self.rng().filter(|_| shuffle))
+    }
+
+    /// Lazily gives the given moves, shuffled like [iter_mov](MoveType::iter_mov).
+    pub(crate) fn iter_list(&mut self, moves: Vec<Move>, shuffle: bool) -> MoveIter {
+        MoveIter::new(Enumeration::List(moves), self.rng().filter(|_| shuffle))
+    }
+
+    /// Whether every move keeps the first and the last position in place.
+    pub(crate) fn keeps_ends(&self) -> (bool, bool) {
         match self {
-            MoveType::Reverse { rng: _, size } | MoveType::Swap { rng: _, size } => {
-                let mut moves: Vec<(usize, usize)> = vec![];
-                for i in 0..(*size - 1) {
-                    for j in (i + 1)..*size {
-                        moves.push((i, j))
-                    }
-                }
-                moves
+            MoveType::Tsp { .. } => (true, false),
+            MoveType::FixedEnds { start, end, .. } => (*start > 0, *end > 0),
+            MoveType::MultiNeighbor { move_types, .. } => move_types
+                .iter()
+                .map(MoveType::keeps_ends)
+                .fold((true, true), |(start, end), (keeps_start, keeps_end)| {
+                    (start && keeps_start, end && keeps_end)
+                }),
+            _ => (false, false),
+        }
+    }
+
+    /// Number of neighborhoods, more than one only for a multi neighborhood.
+    pub(crate) fn neighborhood_count(&self) -> usize {
+        match self {
+            MoveType::MultiNeighbor { move_types, .. } => move_types.len(),
+            _ => 1,
+        }
+    }
+
+    /// The given neighborhood of a multi neighborhood, the move type itself otherwise.
+    pub(crate) fn select(&mut self, neighborhood: Option<usize>) -> &mut MoveType {
+        match (self, neighborhood) {
+            (MoveType::MultiNeighbor { move_types, .. }, Some(neighborhood)) => {
+                &mut move_types[neighborhood]
+            }
+            (move_type, _) => move_type,
+        }
+    }
+
+    fn rng(&mut self) -> Option<&mut SmallRng> {
+        match self {
+            MoveType::Reverse { rng, .. }
+            | MoveType::Swap { rng, .. }
+            | MoveType::Tsp { rng, .. }
+            | MoveType::Relocate { rng, .. }
+            | MoveType::ThreeOpt { rng, .. }
+            | MoveType::BlockExchange { rng, .. }
+            | MoveType::Flip { rng, .. }
+            | MoveType::FlipPair { rng, .. }
+            | MoveType::Assign { rng, .. }
+            | MoveType::MultiNeighbor { rng, .. } => Some(rng.as_mut()),
+            MoveType::FixedEnds { move_type, .. } => move_type.rng(),
+            MoveType::Custom { .. } => None,
+        }
+    }
+
+    fn enumeration(&self) -> Enumeration {
+        match self {
+            MoveType::Reverse { size, .. } => Enumeration::Pairs {
+                start: 0,
+                size: *size,
+                mov: Move::Reverse,
+            },
+            MoveType::Swap { size, .. } => Enumeration::Pairs {
+                start: 0,
+                size: *size,
+                mov: Move::Swap,
+            },
+            MoveType::Tsp { size, .. } => Enumeration::Pairs {
+                start: 1,
+                size: *size,
+                mov: Move::Swap,
+            },
+            MoveType::FlipPair { size, .. } => Enumeration::Pairs {
+                start: 0,
+                size: *size,
+                mov: Move::FlipPair,
+            },
+            MoveType::Relocate { size, max_len, .. } => Enumeration::Relocate {
+                size: *size,
+                max_len: (*max_len).min(size.saturating_sub(1)),
+            },
+            MoveType::ThreeOpt {
+                size,
+                reconnections,
+                ..
+            } => Enumeration::ThreeOpt {
+                size: *size,
+                reconnections: reconnections.clone(),
+            },
+            MoveType::BlockExchange { size, max_len, .. } => Enumeration::BlockExchange {
+                size: *size,
+                max_len: (*max_len).min(size.saturating_sub(1) / 2),
+            },
+            MoveType::Flip { size, .. } => Enumeration::Flip { size: *size },
+            MoveType::Assign { size, domain, .. } => Enumeration::Assign {
+                size: *size,
+                domain: *domain,
+            },
+            MoveType::MultiNeighbor { move_types, .. } => {
+                Enumeration::Multi(move_types.iter().map(MoveType::enumeration).collect())
+            }
+            MoveType::FixedEnds {
+                move_type, start, ..
+            } => Enumeration::Shifted {
+                moves: Box::new(move_type.enumeration()),
+                offset: *start,
+            },
+            MoveType::Custom { neighborhood } => Enumeration::List(
+                neighborhood
+                    .get_all_mov()
+                    .into_iter()
+                    .map(|values| {
+                        Move::Custom(CustomMove {
+                            values,
+                            neighborhood: neighborhood.clone(),
+                        })
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Moves making a city adjacent to one of its candidate cities, `None` if the neighborhood
+    /// can't be restricted. Only reverse and swap neighborhoods can.
+    pub(crate) fn get_candidate_mov(
+        &self,
+        order: &[usize],
+        candidates: &[Vec<usize>],
+    ) -> Option<Vec<Move>> {
+        if !matches!(
+            self,
+            MoveType::Reverse { .. } | MoveType::Swap { .. } | MoveType::Tsp { .. }
+        ) {
+            return None;
+        }
+        let len = order.len();
+        let position = positions(order);
+        let mut pairs: Vec<(usize, usize)> = (0..len)
+            .flat_map(|i| {
+                let position = &position;
+                candidates[order[i]]
+                    .iter()
+                    .flat_map(move |c| self.connecting_pairs(len, i, position[*c]))
+            })
+            .collect();
+        pairs.sort_unstable();
+        pairs.dedup();
+        Some(self.pair_moves(pairs))
+    }
+
+    /// Moves making the element adjacent to one of the candidates, `None` if the neighborhood
+    /// doesn't support it. `position` gives the index of every element in the order.
+    pub(crate) fn get_mov_around(
+        &self,
+        position: &[usize],
+        element: usize,
+        candidates: &[usize],
+    ) -> Option<Vec<Move>> {
+        let (len, i) = (position.len(), position[element]);
+        match self {
+            MoveType::Reverse { .. } | MoveType::Swap { .. } | MoveType::Tsp { .. } => {
+                let mut pairs: Vec<(usize, usize)> = candidates
+                    .iter()
+                    .flat_map(|c| self.connecting_pairs(len, i, position[*c]))
+                    .collect();
+                pairs.sort_unstable();
+                pairs.dedup();
+                Some(self.pair_moves(pairs))
             }
-            MoveType::Tsp { rng: _, size } => {
-                let mut moves: Vec<(usize, usize)> = vec![];
-                for i in 1..(*size - 1) {
-                    for j in (i + 1)..*size {
-                        moves.push((i, j))
+            // The segments starting or ending at the element, put before or after a candidate.
+            MoveType::Relocate { max_len, .. } => {
+                let mut moves = vec![];
+                for segment in 1..=(*max_len).min(len - 1) {
+                    for from in [i, (i + 1).saturating_sub(segment)] {
+                        if from + segment > len {
+                            continue;
+                        }
+                        for j in candidates.iter().map(|c| position[*c]) {
+                            if (from..from + segment).contains(&j) {
+                                continue;
+                            }
+                            let rest = if j < from { j } else { j - segment };
+                            for to in [rest, rest + 1] {
+                                if to != from {
+                                    moves.push((from, to, segment));
+                                }
+                            }
+                        }
                     }
                 }
-                moves
+                moves.sort_unstable();
+                moves.dedup();
+                Some(
+                    moves
+                        .into_iter()
+                        .map(|(from, to, segment)| Move::Relocate(from, to, segment))
+                        .collect(),
+                )
             }
-            MoveType::MultiNeighbor {
-                move_types: _,
-                weights: _,
-            } => {
-                panic!("MultiNeighbor doesn't support get_all_mov")
+            _ => None,
+        }
+    }
+
+    /// Positions of the reverse or swap moves making the elements on positions `i` and `j`
+    /// adjacent.
+    fn connecting_pairs(&self, len: usize, i: usize, j: usize) -> Vec<(usize, usize)> {
+        match self {
+            // Reversing after the first element or before the second one connects both.
+            MoveType::Reverse { .. } => {
+                let (i, j) = (i.min(j), i.max(j));
+                [(i + 1, j), (i, j - 1)]
+                    .into_iter()
+                    .filter(|(i, j)| i < j)
+                    .collect()
+            }
+            // Swapping either element with the one before or after the other.
+            MoveType::Swap { .. } | MoveType::Tsp { .. } => {
+                let start = usize::from(matches!(self, MoveType::Tsp { .. }));
+                [
+                    ((i + 1) % len, j),
+                    ((i + len - 1) % len, j),
+                    ((j + 1) % len, i),
+                    ((j + len - 1) % len, i),
+                ]
+                .into_iter()
+                .map(|(i, j)| (i.min(j), i.max(j)))
+                .filter(|(i, j)| i != j && *i >= start)
+                .collect()
             }
+            _ => vec![],
         }
     }
 
+    fn pair_moves(&self, pairs: Vec<(usize, usize)>) -> Vec<Move> {
+        let reverse = matches!(self, MoveType::Reverse { .. });
+        pairs
+            .into_iter()
+            .map(|(i, j)| {
+                if reverse {
+                    Move::Reverse(i, j)
+                } else {
+                    Move::Swap(i, j)
+                }
+            })
+            .collect()
+    }
+
     pub(crate) fn set_seed(&mut self, seed: u64) {
         match self {
             MoveType::Reverse { rng, size: _ }
             | MoveType::Swap { rng, size: _ }
-            | MoveType::Tsp { rng, size: _ } => {
+            | MoveType::Tsp { rng, size: _ }
+            | MoveType::Relocate { rng, .. }
+            | MoveType::ThreeOpt { rng, .. }
+            | MoveType::BlockExchange { rng, .. }
+            | MoveType::Flip { rng, size: _ }
+            | MoveType::FlipPair { rng, size: _ }
+            | MoveType::Assign { rng, .. } => {
                 *rng = Box::new(SmallRng::seed_from_u64(seed));
             }
             MoveType::MultiNeighbor {
+                rng,
                 move_types,
                 weights: _,
             } => {
+                **rng = SmallRng::seed_from_u64(seed);
                 for mov in move_types {
                     mov.set_seed(seed);
                 }
             }
+            MoveType::FixedEnds { move_type, .. } => move_type.set_seed(seed),
+            MoveType::Custom { neighborhood } => neighborhood.set_seed(seed),
         }
     }
 
@@ -165,37 +832,442 @@ impl MoveType {
         match self {
             MoveType::Reverse { size, .. }
             | MoveType::Swap { size, .. }
-            | MoveType::Tsp { size, .. } => *size = new_size,
+            | MoveType::Tsp { size, .. }
+            | MoveType::Relocate { size, .. }
+            | MoveType::ThreeOpt { size, .. }
+            | MoveType::BlockExchange { size, .. }
+            | MoveType::Flip { size, .. }
+            | MoveType::FlipPair { size, .. }
+            | MoveType::Assign { size, .. } => *size = new_size,
             MoveType::MultiNeighbor { move_types, .. } => {
                 for move_type in move_types {
                     move_type.set_size(new_size);
                 }
             }
+            MoveType::FixedEnds {
+                move_type,
+                start,
+                end,
+            } => move_type.set_size(new_size.saturating_sub(*start + *end)),
+            MoveType::Custom { .. } => (),
+        }
+    }
+
+    pub(crate) fn set_domain(&mut self, new_domain: usize) {
+        match self {
+            MoveType::Assign { domain, .. } => *domain = new_domain,
+            MoveType::MultiNeighbor { move_types, .. } => {
+                for move_type in move_types {
+                    move_type.set_domain(new_domain);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Neighborhood of a [RealVectorProblem](super::RealVectorProblem), every move perturbs a
+/// single coordinate.
+#[derive(Clone)]
+pub enum RealMoveType {
+    Gaussian {
+        rng: Box<SmallRng>,
+        size: usize,
+        step: f64,
+    },
+    Uniform {
+        rng: Box<SmallRng>,
+        size: usize,
+        step: f64,
+    },
+}
+impl RealMoveType {
+    /// Perturbs a random coordinate with normally distributed noise, `step` is the standard
+    /// deviation.
+    pub fn gaussian(step: f64, seed: Option<u64>) -> RealMoveType {
+        RealMoveType::Gaussian {
+            rng: Box::new(rng_from_seed(seed)),
+            size: 0,
+            step,
+        }
+    }
+    /// Perturbs a random coordinate with uniform noise in `-step..step`.
+    pub fn uniform(step: f64, seed: Option<u64>) -> RealMoveType {
+        RealMoveType::Uniform {
+            rng: Box::new(rng_from_seed(seed)),
+            size: 0,
+            step,
+        }
+    }
+
+    pub(crate) fn get_mov(&mut self) -> Move {
+        match self {
+            RealMoveType::Gaussian { rng, size, step } => {
+                // Box-Muller transform, 1 - u keeps the logarithm finite.
+                let u: f64 = rng.gen();
+                let v: f64 = rng.gen();
+                let normal =
+                    (-2.0 * (1.0 - u).ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos();
+                Move::Perturb(rng.gen_range(0..*size), *step * normal)
+            }
+            RealMoveType::Uniform { rng, size, step } => {
+                Move::Perturb(rng.gen_range(0..*size), rng.gen_range(-*step..*step))
+            }
+        }
+    }
+
+    pub(crate) fn get_all_mov(&self) -> Vec<Move> {
+        MoveIter::new(self.enumeration(), None).collect()
+    }
+
+    /// Lazily gives the moves of [get_all_mov](RealMoveType::get_all_mov), shuffled if
+    /// `shuffle`.
+    pub(crate) fn iter_mov(&mut self, shuffle: bool) -> MoveIter {
+        let moves = self.enumeration();
+        let (RealMoveType::Gaussian { rng, .. } | RealMoveType::Uniform { rng, .. }) = self;
+        MoveIter::new(moves, Some(rng.as_mut()).filter(|_| shuffle))
+    }
+
+    fn enumeration(&self) -> Enumeration {
+        let (RealMoveType::Gaussian { size, step, .. } | RealMoveType::Uniform { size, step, .. }) =
+            self;
+        Enumeration::Perturb {
+            size: *size,
+            step: *step,
+        }
+    }
+
+    pub(crate) fn set_seed(&mut self, seed: u64) {
+        let (RealMoveType::Gaussian { rng, .. } | RealMoveType::Uniform { rng, .. }) = self;
+        **rng = SmallRng::seed_from_u64(seed);
+    }
+
+    pub(crate) fn set_size(&mut self, new_size: usize) {
+        let (RealMoveType::Gaussian { size, .. } | RealMoveType::Uniform { size, .. }) = self;
+        *size = new_size;
+    }
+}
+
+/// Lazy enumeration of the moves of a neighborhood. Every move is computed from its index, so
+/// the moves can be visited in a shuffled order without storing them.
+pub(crate) struct MoveIter {
+    moves: Enumeration,
+    count: usize,
+    index: usize,
+    /// The `i`th move visited is move `(stride * i + offset) % count`, a stride coprime with
+    /// the count visits every move once.
+    stride: usize,
+    offset: usize,
+}
+impl MoveIter {
+    fn new(moves: Enumeration, rng: Option<&mut SmallRng>) -> Self {
+        let count = moves.count();
+        let (stride, offset) = match rng {
+            Some(rng) if count > 1 => {
+                let mut stride = rng.gen_range(1..count);
+                while gcd(stride, count) != 1 {
+                    stride = rng.gen_range(1..count);
+                }
+                (stride, rng.gen_range(0..count))
+            }
+            _ => (1, 0),
+        };
+        MoveIter {
+            moves,
+            count,
+            index: 0,
+            stride,
+            offset,
+        }
+    }
+}
+impl Iterator for MoveIter {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Move> {
+        if self.index == self.count {
+            return None;
+        }
+        let index =
+            (self.stride as u128 * self.index as u128 + self.offset as u128) % self.count as u128;
+        self.index += 1;
+        Some(self.moves.get(index as usize))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.count - self.index;
+        (left, Some(left))
+    }
+}
+impl ExactSizeIterator for MoveIter {}
+
+/// All moves of a neighborhood, numbered in lexicographic order of their values.
+enum Enumeration {
+    /// Every pair `i < j` of indices in `start..size`.
+    Pairs {
+        start: usize,
+        size: usize,
+        mov: fn(usize, usize) -> Move,
+    },
+    /// Moving a single element one place back equals moving its predecessor one place
+    /// forward, so only the latter is numbered.
+    Relocate {
+        size: usize,
+        max_len: usize,
+    },
+    ThreeOpt {
+        size: usize,
+        reconnections: Vec<Reconnection>,
+    },
+    BlockExchange {
+        size: usize,
+        max_len: usize,
+    },
+    Flip {
+        size: usize,
+    },
+    Assign {
+        size: usize,
+        domain: usize,
+    },
+    Perturb {
+        size: usize,
+        step: f64,
+    },
+    List(Vec<Move>),
+    Multi(Vec<Enumeration>),
+    /// The moves of another enumeration on the positions `offset` further along.
+    Shifted {
+        moves: Box<Enumeration>,
+        offset: usize,
+    },
+}
+impl Enumeration {
+    fn count(&self) -> usize {
+        match self {
+            Enumeration::Pairs { start, size, .. } => binomial(size.saturating_sub(*start), 2),
+            Enumeration::Relocate { size, max_len } => (1..=*max_len)
+                .map(|len| Enumeration::relocate_count(*size, len))
+                .sum(),
+            Enumeration::ThreeOpt {
+                size,
+                reconnections,
+            } => binomial(*size, 3) * reconnections.len(),
+            Enumeration::BlockExchange { size, max_len } => (1..=*max_len)
+                .map(|len| binomial(size - 2 * len + 1, 2))
+                .sum(),
+            Enumeration::Flip { size } => *size,
+            Enumeration::Assign { size, domain } => size * domain,
+            Enumeration::Perturb { size, .. } => 2 * size,
+            Enumeration::List(moves) => moves.len(),
+            Enumeration::Multi(enumerations) => enumerations.iter().map(Self::count).sum(),
+            Enumeration::Shifted { moves, .. } => moves.count(),
+        }
+    }
+
+    fn relocate_count(size: usize, len: usize) -> usize {
+        let last = size - len;
+        if len == 1 {
+            last * last
+        } else {
+            (last + 1) * last
+        }
+    }
+
+    fn get(&self, mut index: usize) -> Move {
+        match self {
+            Enumeration::Pairs { start, size, mov } => {
+                let (i, j) = lexicographic_pair(index, size - start);
+                mov(i + start, j + start)
+            }
+            Enumeration::Relocate { size, max_len } => {
+                for len in 1..=*max_len {
+                    let count = Enumeration::relocate_count(*size, len);
+                    if index >= count {
+                        index -= count;
+                        continue;
+                    }
+                    let last = size - len;
+                    if len > 1 {
+                        let (from, to) = (index / last, index % last);
+                        return Move::Relocate(from, if to < from { to } else { to + 1 }, len);
+                    }
+                    if index < last {
+                        return Move::Relocate(0, index + 1, 1);
+                    }
+                    let from = 1 + (index - last) / (last - 1);
+                    let to = (index - last) % (last - 1);
+                    return Move::Relocate(from, if to + 1 < from { to } else { to + 2 }, 1);
+                }
+                panic!("Move index out of range")
+            }
+            Enumeration::ThreeOpt {
+                size,
+                reconnections,
+            } => {
+                let (i, j, k) = lexicographic_triple(index / reconnections.len(), *size);
+                let reconnection = reconnections[index % reconnections.len()];
+                Move::ThreeOpt(i + 1, j, k, reconnection)
+            }
+            Enumeration::BlockExchange { size, max_len } => {
+                for len in 1..=*max_len {
+                    let blocks = size - 2 * len + 1;
+                    let count = binomial(blocks, 2);
+                    if index >= count {
+                        index -= count;
+                        continue;
+                    }
+                    let (i, j) = lexicographic_pair(index, blocks);
+                    return Move::BlockExchange(i + 1, j + len, len);
+                }
+                panic!("Move index out of range")
+            }
+            Enumeration::Flip { .. } => Move::Flip(index),
+            Enumeration::Assign { domain, .. } => Move::Assign(index / domain, index % domain),
+            Enumeration::Perturb { step, .. } => {
+                Move::Perturb(index / 2, if index % 2 == 1 { -*step } else { *step })
+            }
+            Enumeration::List(moves) => moves[index].clone(),
+            Enumeration::Multi(enumerations) => {
+                for enumeration in enumerations {
+                    let count = enumeration.count();
+                    if index < count {
+                        return enumeration.get(index);
+                    }
+                    index -= count;
+                }
+                panic!("Move index out of range")
+            }
+            Enumeration::Shifted { moves, offset } => moves.get(index).shifted(*offset),
         }
     }
 }
 
+fn binomial(n: usize, k: usize) -> usize {
+    if n < k {
+        return 0;
+    }
+    (0..k).fold(1, |result, i| result * (n - i) / (i + 1))
+}
+
+/// Largest `n` with `binomial(n, k) <= rank`.
+fn colex_top(rank: usize, k: usize) -> usize {
+    let factorial = (1..=k).product::<usize>() as f64;
+    let mut n = ((rank as f64 * factorial).powf(1.0 / k as f64) as usize).max(k - 1);
+    while binomial(n + 1, k) <= rank {
+        n += 1;
+    }
+    while binomial(n, k) > rank {
+        n -= 1;
+    }
+    n
+}
+
+/// Pair `i < j` of `0..size` with the given index in lexicographic order. Lexicographic order
+/// is colexicographic order of the mirrored pairs, backwards.
+fn lexicographic_pair(index: usize, size: usize) -> (usize, usize) {
+    let mut rank = binomial(size, 2) - 1 - index;
+    let b = colex_top(rank, 2);
+    rank -= binomial(b, 2);
+    (size - 1 - b, size - 1 - rank)
+}
+
+/// Triple `i < j < k` of `0..size` with the given index in lexicographic order.
+fn lexicographic_triple(index: usize, size: usize) -> (usize, usize, usize) {
+    let mut rank = binomial(size, 3) - 1 - index;
+    let c = colex_top(rank, 3);
+    rank -= binomial(c, 3);
+    let b = colex_top(rank, 2);
+    rank -= binomial(b, 2);
+    (size - 1 - c, size - 1 - b, size - 1 - rank)
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Index of every element in the order.
+pub(crate) fn positions(order: &[usize]) -> Vec<usize> {
+    let mut position = vec![0; order.len()];
+    for (i, element) in order.iter().enumerate() {
+        position[*element] = i;
+    }
+    position
+}
+
+/// Seeded rng if a seed is given, otherwise one seeded from entropy.
+fn rng_from_seed(seed: Option<u64>) -> SmallRng {
+    match seed {
+        Some(seed) => SmallRng::seed_from_u64(seed),
+        None => SmallRng::from_entropy(),
+    }
+}
+
+/// Draws two different indices in `0..size`, the smallest one first.
+fn random_pair(rng: &mut SmallRng, size: usize) -> (usize, usize) {
+    let i = rng.gen_range(0..size);
+    let mut j = rng.gen_range(1..size);
+    while i == j {
+        j = rng.gen_range(1..size);
+    }
+    if j < i {
+        return (j, i);
+    }
+    (i, j)
+}
+
 #[cfg(test)]
 mod tests {
-    use std::panic;
+    use std::sync::Arc;
 
     use rand::{rngs::SmallRng, SeedableRng};
 
+    use crate::problem::{
+        ArrayProblem, Evaluation, Move, Neighborhood, Problem, RealMoveType, Reconnection,
+    };
     use crate::MoveType;
+
+    /// Rotates the state to the left by the value of the move.
+    struct Rotate {
+        size: usize,
+    }
+    impl Neighborhood for Rotate {
+        fn get_mov(&self) -> Vec<usize> {
+            vec![1]
+        }
+        fn get_all_mov(&self) -> Vec<Vec<usize>> {
+            (1..self.size).map(|k| vec![k]).collect()
+        }
+        fn do_move(&self, state: &mut [usize], mov: &[usize]) {
+            state.rotate_left(mov[0]);
+        }
+    }
+
     #[test]
     fn reverse_move_type_test() {
         let mut reverse = MoveType::Reverse {
             rng: Box::new(SmallRng::seed_from_u64(0)),
             size: 4,
         };
-        assert_eq!(reverse.get_mov(), (2, 3));
+        assert_eq!(reverse.get_mov(), Move::Reverse(2, 3));
         assert_eq!(
             reverse.get_all_mov(),
-            [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]
+            [
+                Move::Reverse(0, 1),
+                Move::Reverse(0, 2),
+                Move::Reverse(0, 3),
+                Move::Reverse(1, 2),
+                Move::Reverse(1, 3),
+                Move::Reverse(2, 3)
+            ]
         );
 
         let mut array: Vec<usize> = vec![0, 1, 2, 3];
-        reverse.do_move(&mut array, (0, 3));
+        Move::Reverse(0, 3).do_move(&mut array);
         assert_eq!(array, [3, 2, 1, 0])
     }
     #[test]
@@ -204,43 +1276,422 @@ mod tests {
             rng: Box::new(SmallRng::seed_from_u64(0)),
             size: 4,
         };
-        assert_eq!(tsp.get_mov(), (2, 3));
-        assert_eq!(tsp.get_all_mov(), [(1, 2), (1, 3), (2, 3)]);
+        assert_eq!(tsp.get_mov(), Move::Swap(2, 3));
+        assert_eq!(
+            tsp.get_all_mov(),
+            [Move::Swap(1, 2), Move::Swap(1, 3), Move::Swap(2, 3)]
+        );
 
         let mut array: Vec<usize> = vec![0, 1, 2, 3];
-        tsp.do_move(&mut array, (0, 3));
+        Move::Swap(0, 3).do_move(&mut array);
         assert_eq!(array, [3, 1, 2, 0])
     }
     #[test]
+    fn relocate_move_type_test() {
+        let mut relocate = MoveType::relocate(Some(0));
+        relocate.set_size(4);
+        let moves = relocate.get_all_mov();
+        assert_eq!(moves.len(), 9);
+        assert!(!moves.contains(&Move::Relocate(1, 0, 1)));
+        for _ in 0..20 {
+            assert!(matches!(relocate.get_mov(), Move::Relocate(from, to, 1) if from != to));
+        }
+
+        let mut or_opt = MoveType::or_opt(Some(0));
+        or_opt.set_size(5);
+        assert_eq!(or_opt.get_all_mov().len(), 16 + 12 + 6);
+        for _ in 0..20 {
+            let Move::Relocate(from, to, len) = or_opt.get_mov() else {
+                panic!("Or-opt should give relocate moves")
+            };
+            assert!(from != to && (1..=3).contains(&len) && from.max(to) + len <= 5);
+        }
+
+        let mut array: Vec<usize> = vec![0, 1, 2, 3, 4];
+        Move::Relocate(0, 2, 2).do_move(&mut array);
+        assert_eq!(array, [2, 3, 0, 1, 4]);
+        Move::Relocate(4, 1, 1).do_move(&mut array);
+        assert_eq!(array, [2, 4, 3, 0, 1]);
+        Move::Relocate(4, 1, 1).undo_move(&mut array);
+        Move::Relocate(0, 2, 2).undo_move(&mut array);
+        assert_eq!(array, [0, 1, 2, 3, 4]);
+    }
+    #[test]
+    fn three_opt_move_type_test() {
+        let mut three_opt = MoveType::three_opt(Some(0));
+        three_opt.set_size(6);
+        // Cuts 1 <= i <= j < k <= 5 for every reconnection.
+        assert_eq!(three_opt.get_all_mov().len(), 20 * 4);
+        let mut or_3opt = MoveType::or_3opt(Some(0));
+        or_3opt.set_size(6);
+        assert_eq!(or_3opt.get_all_mov().len(), 20);
+
+        let expected = [
+            [0, 2, 1, 4, 3, 5],
+            [0, 3, 4, 1, 2, 5],
+            [0, 4, 3, 1, 2, 5],
+            [0, 3, 4, 2, 1, 5],
+        ];
+        for (reconnection, expected) in Reconnection::ALL.into_iter().zip(expected) {
+            let mut array: Vec<usize> = (0..6).collect();
+            let mov = Move::ThreeOpt(1, 2, 4, reconnection);
+            mov.do_move(&mut array);
+            assert_eq!(array, expected);
+            mov.undo_move(&mut array);
+            assert_eq!(array, [0, 1, 2, 3, 4, 5]);
+        }
+        for _ in 0..20 {
+            let Move::ThreeOpt(i, j, k, _) = three_opt.get_mov() else {
+                panic!("3-opt should give 3-opt moves")
+            };
+            assert!(1 <= i && i <= j && j < k && k < 6);
+        }
+        // The last cut can fall on the last position, like in the enumeration.
+        assert!((0..100).any(|_| matches!(three_opt.get_mov(), Move::ThreeOpt(_, _, 5, _))));
+    }
+    #[test]
+    fn block_exchange_move_type_test() {
+        let mut block_exchange = MoveType::block_exchange(2, Some(0));
+        block_exchange.set_size(6);
+        assert_eq!(block_exchange.get_all_mov().len(), 10 + 3);
+        for _ in 0..20 {
+            let Move::BlockExchange(i, j, len) = block_exchange.get_mov() else {
+                panic!("Block exchange should give block exchange moves")
+            };
+            assert!(i >= 1 && i + len <= j && j + len <= 6);
+        }
+        let mut array: Vec<usize> = (0..6).collect();
+        Move::BlockExchange(1, 4, 2).do_move(&mut array);
+        assert_eq!(array, [0, 4, 5, 3, 1, 2]);
+        Move::BlockExchange(1, 4, 2).undo_move(&mut array);
+        assert_eq!(array, [0, 1, 2, 3, 4, 5]);
+    }
+    #[test]
+    #[should_panic(expected = "Blocks need at least one element")]
+    fn empty_block_exchange_test() {
+        MoveType::block_exchange(0, None);
+    }
+    #[test]
+    fn sampled_moves_test() {
+        let move_types = [
+            MoveType::reverse(Some(0)),
+            MoveType::swap(Some(0)),
+            MoveType::tsp(Some(0)),
+            MoveType::three_opt(Some(0)),
+            MoveType::or_3opt(Some(0)),
+            MoveType::block_exchange(2, Some(0)),
+            MoveType::fixed_ends(MoveType::three_opt(Some(0)), true, true),
+            MoveType::relocate(Some(0)),
+            MoveType::or_opt(Some(0)),
+        ];
+        for mut move_type in move_types {
+            move_type.set_size(7);
+            let moves = move_type.get_all_mov();
+            // Moving an element one place back is numbered as moving its predecessor forward.
+            let orders: Vec<Vec<usize>> = moves
+                .iter()
+                .map(|mov| {
+                    let mut array: Vec<usize> = (0..7).collect();
+                    mov.do_move(&mut array);
+                    array
+                })
+                .collect();
+            for _ in 0..200 {
+                let mov = move_type.get_mov();
+                if let Move::Relocate(..) = mov {
+                    let mut array: Vec<usize> = (0..7).collect();
+                    mov.do_move(&mut array);
+                    assert!(orders.contains(&array), "{mov:?} isn't enumerated");
+                } else {
+                    assert!(moves.contains(&mov), "{mov:?} isn't enumerated");
+                }
+            }
+        }
+    }
+    #[test]
+    #[should_panic(expected = "3-opt moves need at least 3 elements")]
+    fn small_three_opt_test() {
+        let mut three_opt = MoveType::three_opt(Some(0));
+        three_opt.set_size(2);
+        assert!(three_opt.get_all_mov().is_empty());
+        three_opt.get_mov();
+    }
+    #[test]
+    #[should_panic(expected = "Relocate moves need at least 2 elements")]
+    fn small_relocate_test() {
+        let mut relocate = MoveType::relocate(Some(0));
+        relocate.set_size(1);
+        assert!(relocate.get_all_mov().is_empty());
+        relocate.get_mov();
+    }
+    #[test]
+    fn fixed_ends_move_type_test() {
+        let mut fixed = MoveType::fixed_ends(MoveType::or_opt(Some(0)), true, true);
+        fixed.set_size(7);
+        let mut moves = fixed.get_all_mov();
+        assert_eq!(moves.len(), 16 + 12 + 6);
+        assert!(moves.contains(&Move::Relocate(1, 5, 1)));
+        for _ in 0..20 {
+            moves.push(fixed.get_mov());
+        }
+        for mov in moves {
+            let mut array: Vec<usize> = (0..7).collect();
+            mov.do_move(&mut array);
+            assert_eq!((array[0], array[6]), (0, 6));
+        }
+        assert_eq!(fixed.keeps_ends(), (true, true));
+
+        let mut fixed = MoveType::fixed_ends(MoveType::swap(Some(0)), false, true);
+        fixed.set_size(4);
+        assert_eq!(
+            fixed.get_all_mov(),
+            [Move::Swap(0, 1), Move::Swap(0, 2), Move::Swap(1, 2)]
+        );
+        let multi = MoveType::multi_neighbor(vec![fixed, MoveType::tsp(None)], None, None);
+        assert_eq!(multi.keeps_ends(), (false, false));
+    }
+    #[test]
+    fn candidate_move_test() {
+        let order = [0, 1, 2, 3, 4, 5];
+        let candidates = vec![vec![3], vec![], vec![], vec![], vec![], vec![]];
+        let mut reverse = MoveType::reverse(Some(0));
+        reverse.set_size(6);
+        assert_eq!(
+            reverse.get_candidate_mov(&order, &candidates),
+            Some(vec![Move::Reverse(0, 2), Move::Reverse(1, 3)])
+        );
+        let mut swap = MoveType::swap(Some(0));
+        swap.set_size(6);
+        assert_eq!(
+            swap.get_candidate_mov(&order, &candidates),
+            Some(vec![
+                Move::Swap(0, 2),
+                Move::Swap(0, 4),
+                Move::Swap(1, 3),
+                Move::Swap(3, 5)
+            ])
+        );
+        let mut tsp = MoveType::tsp(Some(0));
+        tsp.set_size(6);
+        let candidates = vec![vec![], vec![], vec![], vec![0], vec![], vec![]];
+        assert_eq!(
+            tsp.get_candidate_mov(&order, &candidates),
+            Some(vec![Move::Swap(1, 3), Move::Swap(3, 5)])
+        );
+        assert_eq!(
+            MoveType::flip(None).get_candidate_mov(&order, &candidates),
+            None
+        );
+    }
+    #[test]
+    fn move_iter_test() {
+        for size in [2, 5, 9] {
+            let mut expected = vec![];
+            for i in 0..size {
+                for j in i + 1..size {
+                    expected.push(Move::Reverse(i, j));
+                }
+            }
+            for len in 1..=3.min(size - 1) {
+                for from in 0..=size - len {
+                    for to in (0..=size - len).filter(|to| *to != from) {
+                        if len > 1 || to + 1 != from {
+                            expected.push(Move::Relocate(from, to, len));
+                        }
+                    }
+                }
+            }
+            for i in 1..size {
+                for j in i..size {
+                    for k in j + 1..size {
+                        for reconnection in Reconnection::ALL {
+                            expected.push(Move::ThreeOpt(i, j, k, reconnection));
+                        }
+                    }
+                }
+            }
+            for len in 1..=2.min((size - 1) / 2) {
+                for i in 1..=size - 2 * len {
+                    for j in i + len..=size - len {
+                        expected.push(Move::BlockExchange(i, j, len));
+                    }
+                }
+            }
+
+            let mut move_type = MoveType::multi_neighbor(
+                vec![
+                    MoveType::reverse(Some(0)),
+                    MoveType::or_opt(Some(0)),
+                    MoveType::three_opt(Some(0)),
+                    MoveType::block_exchange(2, Some(0)),
+                ],
+                None,
+                Some(0),
+            );
+            move_type.set_size(size);
+            assert_eq!(move_type.get_all_mov(), expected);
+            assert_eq!(move_type.iter_mov(false).len(), expected.len());
+
+            let shuffled: Vec<Move> = move_type.iter_mov(true).collect();
+            assert_eq!(shuffled.len(), expected.len());
+            assert!(expected.iter().all(|mov| shuffled.contains(mov)));
+            if size > 2 {
+                assert_ne!(shuffled, expected);
+            }
+        }
+    }
+    #[test]
     fn swap_move_type_test() {
         let mut swap = MoveType::Swap {
             rng: Box::new(SmallRng::seed_from_u64(0)),
             size: 4,
         };
-        assert_eq!(swap.get_mov(), (2, 3));
+        assert_eq!(swap.get_mov(), Move::Swap(2, 3));
         assert_eq!(
             swap.get_all_mov(),
-            [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]
+            [
+                Move::Swap(0, 1),
+                Move::Swap(0, 2),
+                Move::Swap(0, 3),
+                Move::Swap(1, 2),
+                Move::Swap(1, 3),
+                Move::Swap(2, 3)
+            ]
         );
 
         let mut array: Vec<usize> = vec![0, 1, 2, 3];
-        swap.do_move(&mut array, (0, 3));
+        Move::Swap(0, 3).do_move(&mut array);
         assert_eq!(array, [3, 1, 2, 0])
     }
     #[test]
-    fn multi_move_type_test() {
-        let multi = MoveType::MultiNeighbor {
-            move_types: vec![],
-            weights: vec![],
+    fn flip_move_type_test() {
+        let mut flip = MoveType::Flip {
+            rng: Box::new(SmallRng::seed_from_u64(0)),
+            size: 3,
+        };
+        assert!(matches!(flip.get_mov(), Move::Flip(i) if i < 3));
+        assert_eq!(
+            flip.get_all_mov(),
+            [Move::Flip(0), Move::Flip(1), Move::Flip(2)]
+        );
+
+        let mut flip_pair = MoveType::FlipPair {
+            rng: Box::new(SmallRng::seed_from_u64(0)),
+            size: 3,
+        };
+        assert!(matches!(flip_pair.get_mov(), Move::FlipPair(i, j) if i < j && j < 3));
+        assert_eq!(
+            flip_pair.get_all_mov(),
+            [
+                Move::FlipPair(0, 1),
+                Move::FlipPair(0, 2),
+                Move::FlipPair(1, 2)
+            ]
+        );
+    }
+    #[test]
+    fn assign_move_type_test() {
+        let mut assign = MoveType::Assign {
+            rng: Box::new(SmallRng::seed_from_u64(0)),
+            size: 2,
+            domain: 2,
         };
-        let get_all_mov = panic::catch_unwind(|| multi.get_all_mov());
-        let get_mov = panic::catch_unwind(|| multi.clone().get_mov());
-        let get_do_mov = panic::catch_unwind(|| {
-            let mut array: Vec<usize> = vec![0, 1, 2, 3];
-            multi.clone().do_move(&mut array, (0, 1))
-        });
-        assert!(get_all_mov.is_err());
-        assert!(get_mov.is_err());
-        assert!(get_do_mov.is_err());
+        assert!(matches!(assign.get_mov(), Move::Assign(i, v) if i < 2 && v < 2));
+        assert_eq!(
+            assign.get_all_mov(),
+            [
+                Move::Assign(0, 0),
+                Move::Assign(0, 1),
+                Move::Assign(1, 0),
+                Move::Assign(1, 1)
+            ]
+        );
+    }
+    #[test]
+    fn perturb_move_type_test() {
+        let mut gaussian = RealMoveType::gaussian(0.5, Some(0));
+        gaussian.set_size(3);
+        assert!(matches!(gaussian.get_mov(), Move::Perturb(i, step) if i < 3 && step.is_finite()));
+
+        let mut uniform = RealMoveType::uniform(0.5, Some(0));
+        uniform.set_size(2);
+        assert!(matches!(uniform.get_mov(), Move::Perturb(i, step) if i < 2 && step.abs() < 0.5));
+        assert_eq!(
+            uniform.get_all_mov(),
+            [
+                Move::Perturb(0, 0.5),
+                Move::Perturb(0, -0.5),
+                Move::Perturb(1, 0.5),
+                Move::Perturb(1, -0.5)
+            ]
+        );
+    }
+    #[test]
+    fn undo_move_test() {
+        let mut array: Vec<usize> = vec![0, 1, 2, 3, 4];
+        for mov in [Move::Swap(1, 3), Move::Reverse(0, 4), Move::Reverse(1, 2)] {
+            mov.do_move(&mut array);
+            mov.undo_move(&mut array);
+            assert_eq!(array, [0, 1, 2, 3, 4]);
+        }
+    }
+    #[test]
+    fn multi_move_type_test() {
+        let mut multi = MoveType::multi_neighbor(
+            vec![MoveType::swap(Some(0)), MoveType::reverse(Some(0))],
+            Some(vec![3.0, 1.0]),
+            Some(0),
+        );
+        multi.set_size(4);
+        assert_eq!(multi.get_all_mov().len(), 12);
+        let swaps = (0..1000)
+            .filter(|_| matches!(multi.get_mov(), Move::Swap(..)))
+            .count();
+        assert!((700..800).contains(&swaps));
+
+        let mut only_reverse = MoveType::multi_neighbor(
+            vec![MoveType::swap(Some(0)), MoveType::reverse(Some(0))],
+            Some(vec![0.0, 1.0]),
+            Some(0),
+        );
+        only_reverse.set_size(4);
+        assert!((0..100).all(|_| matches!(only_reverse.get_mov(), Move::Reverse(..))));
+    }
+    #[test]
+    fn custom_move_type_test() {
+        let mut move_type = MoveType::custom(Arc::new(Rotate { size: 4 }));
+        let moves = move_type.get_all_mov();
+        assert_eq!(moves.len(), 3);
+        assert_eq!(moves[0], move_type.get_mov());
+        assert_ne!(
+            moves[0],
+            MoveType::custom(Arc::new(Rotate { size: 4 })).get_mov()
+        );
+
+        let eval = Evaluation::qap(
+            vec![
+                vec![0.0, 2.0, 9.0, 5.0],
+                vec![2.0, 0.0, 4.0, 6.0],
+                vec![9.0, 4.0, 0.0, 3.0],
+                vec![5.0, 6.0, 3.0, 0.0],
+            ],
+            vec![
+                vec![0.0, 2.0, 0.0, 0.0],
+                vec![2.0, 0.0, 4.0, 0.0],
+                vec![0.0, 4.0, 0.0, 8.0],
+                vec![0.0, 0.0, 8.0, 0.0],
+            ],
+        );
+        let mut problem = ArrayProblem::new(&move_type, &eval);
+        for mov in &moves {
+            let before = problem.eval();
+            let delta = problem.delta_eval(mov);
+            problem.do_mov(mov);
+            assert_eq!(problem.eval() - before, delta);
+            problem.undo_mov(mov);
+            assert_eq!(*problem.state(), [0, 1, 2, 3]);
+        }
+        problem.do_mov(&moves[2]);
+        assert_eq!(*problem.state(), [3, 0, 1, 2]);
     }
 }
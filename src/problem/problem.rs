@@ -1,4 +1,4 @@
-use super::{Evaluation, MoveType};
+use super::{Move, State};
 
 pub trait Problem: Send {
     /// Get a random move
@@ -8,7 +8,7 @@ pub trait Problem: Send {
     /// ```
     ///# use rand::rngs::SmallRng;
     ///# use rand::SeedableRng;
-    ///# use lclPyO3::problem::{ArrayProblem, Evaluation, Problem};
+    ///# use lclPyO3::problem::{ArrayProblem, Evaluation, Move, Problem};
     ///# use lclPyO3::problem::MoveType::Tsp;
     ///# use lclPyO3::termination::TerminationFunction;
     ///    let distance_matrix: Vec<Vec<f64>> = vec![
@@ -21,9 +21,9 @@ pub trait Problem: Send {
     ///     &Tsp {rng:Box::new(SmallRng::seed_from_u64(0)),size:4},
     ///     &Evaluation::Tsp {distance_matrix,symmetric:true});
     ///
-    /// assert_eq!((2,3), problem.get_mov())
+    /// assert_eq!(Move::Swap(2,3), problem.get_mov())
     /// ```
-    fn get_mov(&mut self) -> (usize, usize);
+    fn get_mov(&mut self) -> Move;
 
     /// Get all possible moves
     ///
@@ -32,7 +32,7 @@ pub trait Problem: Send {
     /// ```
     ///# use rand::rngs::SmallRng;
     ///# use rand::SeedableRng;
-    ///# use lclPyO3::problem::{ArrayProblem, Evaluation, Problem};
+    ///# use lclPyO3::problem::{ArrayProblem, Evaluation, Move, Problem};
     ///# use lclPyO3::problem::MoveType::Tsp;
     ///    let distance_matrix: Vec<Vec<f64>> = vec![
     ///        vec![0.0, 2.0, 5.0, 8.0],
@@ -43,11 +43,33 @@ pub trait Problem: Send {
     /// let mut problem = ArrayProblem::new(
     ///     &Tsp {rng:Box::new(SmallRng::seed_from_u64(0)),size:4},
     ///     &Evaluation::Tsp {distance_matrix,symmetric:true});
-    /// let solution:Vec<(usize,usize)>=vec![(1,2),(1,3),(2,3)];
+    /// let solution:Vec<Move>=vec![Move::Swap(1,2),Move::Swap(1,3),Move::Swap(2,3)];
     ///
     /// assert_eq!(solution, problem.get_all_mov())
     /// ```
-    fn get_all_mov(&mut self) -> Vec<(usize, usize)>;
+    fn get_all_mov(&mut self) -> Vec<Move>;
+
+    /// Lazily gives the moves of [get_all_mov](Problem::get_all_mov), or only those of the
+    /// given neighborhood of a multi neighborhood, in a random order if `shuffle`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    ///# use lclPyO3::problem::{ArrayProblem, Evaluation, Move, MoveType, Problem};
+    /// let mut problem = ArrayProblem::new(
+    ///     &MoveType::multi_neighbor(vec![MoveType::swap(Some(0)), MoveType::reverse(Some(0))], None, None),
+    ///     &Evaluation::tsp(vec![vec![0.0; 4]; 4]));
+    /// let reverse: Vec<Move> = problem.iter_mov(Some(1), false).collect();
+    ///
+    /// assert_eq!(reverse.len(), 6);
+    /// assert_eq!(reverse[0], Move::Reverse(0, 1));
+    /// assert_eq!(problem.iter_mov(None, true).count(), 12);
+    /// ```
+    fn iter_mov(
+        &mut self,
+        neighborhood: Option<usize>,
+        shuffle: bool,
+    ) -> Box<dyn Iterator<Item = Move>>;
 
     /// Execute the given move
     ///
@@ -56,7 +78,7 @@ pub trait Problem: Send {
     /// ```
     ///# use rand::rngs::SmallRng;
     ///# use rand::SeedableRng;
-    ///# use lclPyO3::problem::{ArrayProblem, Evaluation, Problem};
+    ///# use lclPyO3::problem::{ArrayProblem, Evaluation, Move, Problem};
     ///# use lclPyO3::problem::MoveType::Tsp;
     ///    let distance_matrix: Vec<Vec<f64>> = vec![
     ///        vec![0.0, 2.0, 5.0, 8.0],
@@ -67,11 +89,36 @@ pub trait Problem: Send {
     /// let mut problem = ArrayProblem::new(
     ///     &Tsp {rng:Box::new(SmallRng::seed_from_u64(0)),size:4},
     ///     &Evaluation::Tsp {distance_matrix,symmetric:true});
-    /// problem.do_mov((1,2),None);
+    /// problem.do_mov(&Move::Swap(1,2));
     ///
     /// assert_eq!(*problem.state(), [0,2,1,3])
     /// ```
-    fn do_mov(&mut self, indices: (usize, usize), move_type: Option<&MoveType>);
+    fn do_mov(&mut self, mov: &Move);
+
+    /// Reverts the given move, which must be the last move executed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    ///# use rand::rngs::SmallRng;
+    ///# use rand::SeedableRng;
+    ///# use lclPyO3::problem::{ArrayProblem, Evaluation, Move, Problem};
+    ///# use lclPyO3::problem::MoveType::Tsp;
+    ///    let distance_matrix: Vec<Vec<f64>> = vec![
+    ///        vec![0.0, 2.0, 5.0, 8.0],
+    ///        vec![2.0, 0.0, 4.0, 1.0],
+    ///        vec![5.0, 4.0, 0.0, 7.0],
+    ///        vec![8.0, 1.0, 7.0, 0.0],
+    ///    ];
+    /// let mut problem = ArrayProblem::new(
+    ///     &Tsp {rng:Box::new(SmallRng::seed_from_u64(0)),size:4},
+    ///     &Evaluation::Tsp {distance_matrix,symmetric:true});
+    /// problem.do_mov(&Move::Reverse(1,3));
+    /// problem.undo_mov(&Move::Reverse(1,3));
+    ///
+    /// assert_eq!(*problem.state(), [0,1,2,3])
+    /// ```
+    fn undo_mov(&mut self, mov: &Move);
 
     /// Gives the change in score if the given move would be performed.
     /// Also optimized. It only calculates what's necessary.
@@ -81,7 +128,7 @@ pub trait Problem: Send {
     /// ```
     ///# use rand::rngs::SmallRng;
     ///# use rand::SeedableRng;
-    ///# use lclPyO3::problem::{ArrayProblem, Evaluation, MoveType, Problem};
+    ///# use lclPyO3::problem::{ArrayProblem, Evaluation, Move, MoveType, Problem};
     ///# use lclPyO3::problem::MoveType::Tsp;
     ///
     ///    let distance_matrix: Vec<Vec<f64>> = vec![
@@ -95,13 +142,13 @@ pub trait Problem: Send {
     ///     &MoveType::tsp(Some(0)),
     ///     &Evaluation::tsp(distance_matrix));
     /// let before=problem.eval();
-    /// let res=problem.delta_eval((1,2),None);
-    /// problem.do_mov((1,2),None);
+    /// let res=problem.delta_eval(&Move::Swap(1,2));
+    /// problem.do_mov(&Move::Swap(1,2));
     /// let after=problem.eval();
     ///
     /// assert_eq!(res, after  - before )
     /// ```
-    fn delta_eval(&mut self, indices: (usize, usize), move_type: Option<&MoveType>) -> f64;
+    fn delta_eval(&mut self, mov: &Move) -> f64;
 
     /// Calculates the current score.
     ///
@@ -110,7 +157,7 @@ pub trait Problem: Send {
     /// ```
     ///# use rand::rngs::SmallRng;
     ///# use rand::SeedableRng;
-    ///# use lclPyO3::problem::{ArrayProblem, Evaluation, MoveType, Problem};
+    ///# use lclPyO3::problem::{ArrayProblem, Evaluation, Move, MoveType, Problem};
     ///# use lclPyO3::problem::MoveType::Tsp;
     ///    let distance_matrix: Vec<Vec<f64>> = vec![
     ///        vec![0.0, 2.0, 5.0, 8.0],
@@ -122,8 +169,8 @@ pub trait Problem: Send {
     ///     &MoveType::tsp(Some(0)),
     ///     &Evaluation::tsp(distance_matrix));
     /// let before=problem.eval();
-    /// let res=problem.delta_eval((1,2),None);
-    /// problem.do_mov((1,2),None);
+    /// let res=problem.delta_eval(&Move::Swap(1,2));
+    /// problem.do_mov(&Move::Swap(1,2));
     /// let after=problem.eval();
     ///
     /// assert_eq!(res, after - before )
@@ -137,7 +184,7 @@ pub trait Problem: Send {
     /// ```
     ///# use rand::rngs::SmallRng;
     ///# use rand::SeedableRng;
-    ///# use lclPyO3::problem::{ArrayProblem, Evaluation, MoveType, Problem};
+    ///# use lclPyO3::problem::{ArrayProblem, Evaluation, Move, MoveType, Problem};
     ///# use lclPyO3::problem::MoveType::Tsp;
     ///    let distance_matrix: Vec<Vec<f64>> = vec![
     ///        vec![0.0, 2.0, 5.0, 8.0],
@@ -148,7 +195,7 @@ pub trait Problem: Send {
     /// let mut problem = ArrayProblem::new(
     ///     &MoveType::tsp(Some(0)),
     ///     &Evaluation::tsp(distance_matrix));
-    /// problem.do_mov((1,2),None);
+    /// problem.do_mov(&Move::Swap(1,2));
     /// problem.reset();
     ///
     /// assert_eq!(*problem.state(), [0,1,2,3])
@@ -162,7 +209,7 @@ pub trait Problem: Send {
     /// ```
     ///# use rand::rngs::SmallRng;
     ///# use rand::SeedableRng;
-    ///# use lclPyO3::problem::{ArrayProblem, Evaluation, MoveType, Problem};
+    ///# use lclPyO3::problem::{ArrayProblem, Evaluation, Move, MoveType, Problem};
     ///# use lclPyO3::problem::MoveType::Tsp;
     ///    let distance_matrix: Vec<Vec<f64>> = vec![
     ///        vec![0.0, 2.0, 5.0, 8.0],
@@ -173,7 +220,7 @@ pub trait Problem: Send {
     /// let mut problem = ArrayProblem::new(
     ///     &MoveType::tsp(Some(0)),
     ///     &Evaluation::tsp(distance_matrix));
-    /// problem.do_mov((1,2),None);
+    /// problem.do_mov(&Move::Swap(1,2));
     /// problem.set_best();
     ///
     /// assert_eq!(*problem.best_solution(), [0,2,1,3])
@@ -187,7 +234,7 @@ pub trait Problem: Send {
     /// ```
     ///# use rand::rngs::SmallRng;
     ///# use rand::SeedableRng;
-    ///# use lclPyO3::problem::{ArrayProblem, Evaluation, MoveType, Problem};
+    ///# use lclPyO3::problem::{ArrayProblem, Evaluation, Move, MoveType, Problem};
     ///# use lclPyO3::problem::MoveType::Tsp;
     ///    let distance_matrix: Vec<Vec<f64>> = vec![
     ///        vec![0.0, 2.0, 5.0, 8.0],
@@ -203,14 +250,80 @@ pub trait Problem: Send {
     /// ```
     fn hash(&self) -> u64;
 
-    /// Gives the move-type the current problem uses. Used for variable neighborhood search.
-    fn get_move_type(&self) -> &MoveType;
-
-    /// Sets the move type
-    fn set_move_type(&mut self, move_type: MoveType);
+    /// Number of neighborhoods the move type combines. Used for variable neighborhood search.
+    fn neighborhood_count(&self) -> usize;
 
-    /// Sets the evaluation type
-    fn set_eval_type(&mut self, eval_type: Evaluation);
     /// Sets the seed of the underlying MoveType
     fn set_seed(&mut self, seed: u64);
+
+    /// Gives the current state, None if the problem doesn't expose it.
+    fn get_state(&self) -> Option<State>;
+
+    /// Gives the best state found, None if the problem doesn't expose it.
+    fn get_best_solution(&self) -> Option<State>;
+
+    /// Replaces the current state, fails if it isn't a valid state of this problem.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    ///# use lclPyO3::problem::{ArrayProblem, Evaluation, MoveType, Problem, State};
+    ///    let distance_matrix: Vec<Vec<f64>> = vec![
+    ///        vec![0.0, 2.0, 5.0, 8.0],
+    ///        vec![2.0, 0.0, 4.0, 1.0],
+    ///        vec![5.0, 4.0, 0.0, 7.0],
+    ///        vec![8.0, 1.0, 7.0, 0.0],
+    ///    ];
+    /// let mut problem = ArrayProblem::new(
+    ///     &MoveType::tsp(Some(0)),
+    ///     &Evaluation::tsp(distance_matrix));
+    ///
+    /// assert!(problem.set_state(&State::Permutation(vec![0, 1, 1, 3])).is_err());
+    /// problem.set_state(&State::Permutation(vec![0, 2, 1, 3])).unwrap();
+    /// assert_eq!(problem.get_state(), Some(State::Permutation(vec![0, 2, 1, 3])))
+    /// ```
+    fn set_state(&mut self, state: &State) -> Result<(), String>;
+
+    /// Gives the score of the given state without changing the problem, fails if it isn't a
+    /// valid state of this problem.
+    fn eval_state(&self, state: &State) -> Result<f64, String>;
+
+    /// Sets the state [reset](Problem::reset) restores, None restores the usual initial state.
+    /// Fails if it isn't a valid state of this problem.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    ///# use lclPyO3::problem::{ArrayProblem, Evaluation, MoveType, Problem, State};
+    ///    let distance_matrix: Vec<Vec<f64>> = vec![
+    ///        vec![0.0, 2.0, 5.0, 8.0],
+    ///        vec![2.0, 0.0, 4.0, 1.0],
+    ///        vec![5.0, 4.0, 0.0, 7.0],
+    ///        vec![8.0, 1.0, 7.0, 0.0],
+    ///    ];
+    /// let mut problem = ArrayProblem::new(
+    ///     &MoveType::tsp(Some(0)),
+    ///     &Evaluation::tsp(distance_matrix));
+    /// let warm_start = State::Permutation(vec![0, 2, 1, 3]);
+    /// problem.set_warm_start(Some(warm_start.clone())).unwrap();
+    /// problem.reset();
+    ///
+    /// assert_eq!(problem.get_state(), Some(warm_start))
+    /// ```
+    fn set_warm_start(&mut self, warm_start: Option<State>) -> Result<(), String>;
+}
+
+/// A problem that can give the moves around a single element, searched by
+/// [FirstImprovement](crate::local_search::FirstImprovement).
+pub trait ElementProblem: Problem {
+    /// Number of elements [get_mov_around](ElementProblem::get_mov_around) works on, 0 if the
+    /// move type can't give the moves around a single element.
+    fn element_count(&self) -> usize;
+
+    /// Gives the moves changing the neighbors of the given element.
+    fn get_mov_around(&mut self, element: usize) -> Vec<Move>;
+
+    /// Gives the elements whose neighbors change when the move is done, called before doing
+    /// it.
+    fn touched_elements(&self, mov: &Move) -> Vec<usize>;
 }
@@ -0,0 +1,121 @@
+use std::f64::consts::{E, PI};
+
+/// Evaluation of a vector of real numbers, used by a
+/// [RealVectorProblem](super::RealVectorProblem).
+#[derive(Clone)]
+pub enum RealEvaluation {
+    Sphere { dimension: usize },
+    Rastrigin { dimension: usize },
+    Rosenbrock { dimension: usize },
+    Schwefel { dimension: usize },
+    Ackley { dimension: usize },
+}
+impl RealEvaluation {
+    /// Sphere function to be minimized on a real vector: the sum of all squares.
+    pub fn sphere(dimension: usize) -> RealEvaluation {
+        RealEvaluation::Sphere { dimension }
+    }
+    /// Rastrigin function to be minimized on a real vector, minimum 0 in the origin.
+    pub fn rastrigin(dimension: usize) -> RealEvaluation {
+        RealEvaluation::Rastrigin { dimension }
+    }
+    /// Rosenbrock function to be minimized on a real vector, minimum 0 in (1, ..., 1).
+    pub fn rosenbrock(dimension: usize) -> RealEvaluation {
+        RealEvaluation::Rosenbrock { dimension }
+    }
+    /// Schwefel function to be minimized on a real vector, minimum 0 in (420.9687, ...).
+    pub fn schwefel(dimension: usize) -> RealEvaluation {
+        RealEvaluation::Schwefel { dimension }
+    }
+    /// Ackley function to be minimized on a real vector, minimum 0 in the origin.
+    pub fn ackley(dimension: usize) -> RealEvaluation {
+        RealEvaluation::Ackley { dimension }
+    }
+
+    /// Calculates the score of a real vector.
+    pub(crate) fn eval(&self, x: &[f64]) -> f64 {
+        match self {
+            RealEvaluation::Rosenbrock { .. } => x
+                .windows(2)
+                .map(|w| 100.0 * (w[1] - w[0] * w[0]).powi(2) + (1.0 - w[0]).powi(2))
+                .sum(),
+            RealEvaluation::Ackley { .. } => {
+                let n = x.len() as f64;
+                let squares: f64 = x.iter().map(|x| x * x).sum();
+                let cosines: f64 = x.iter().map(|x| (2.0 * PI * x).cos()).sum();
+                -20.0 * (-0.2 * (squares / n).sqrt()).exp() - (cosines / n).exp() + 20.0 + E
+            }
+            RealEvaluation::Sphere { .. }
+            | RealEvaluation::Rastrigin { .. }
+            | RealEvaluation::Schwefel { .. } => {
+                self.real_offset(x.len()) + x.iter().map(|x| self.real_term(*x)).sum::<f64>()
+            }
+        }
+    }
+
+    /// Change in score of a real vector when coordinate `index` gets `value`.
+    pub(crate) fn delta(&self, x: &mut [f64], index: usize, value: f64) -> f64 {
+        match self {
+            RealEvaluation::Sphere { .. }
+            | RealEvaluation::Rastrigin { .. }
+            | RealEvaluation::Schwefel { .. } => self.real_term(value) - self.real_term(x[index]),
+            RealEvaluation::Rosenbrock { .. } => {
+                let start = index.saturating_sub(1);
+                let end = (index + 2).min(x.len());
+                let first = self.eval(&x[start..end]);
+                let old = x[index];
+                x[index] = value;
+                let sec = self.eval(&x[start..end]);
+                x[index] = old;
+                sec - first
+            }
+            _ => {
+                let first = self.eval(x);
+                let old = x[index];
+                x[index] = value;
+                let sec = self.eval(x);
+                x[index] = old;
+                sec - first
+            }
+        }
+    }
+
+    /// Contribution of a single coordinate to a separable real function.
+    fn real_term(&self, x: f64) -> f64 {
+        match self {
+            RealEvaluation::Sphere { .. } => x * x,
+            RealEvaluation::Rastrigin { .. } => x * x - 10.0 * (2.0 * PI * x).cos(),
+            RealEvaluation::Schwefel { .. } => -x * x.abs().sqrt().sin(),
+            _ => panic!("Only separable real evaluations have a term per coordinate"),
+        }
+    }
+
+    /// Constant part of a separable real function.
+    fn real_offset(&self, dimension: usize) -> f64 {
+        match self {
+            RealEvaluation::Rastrigin { .. } => 10.0 * dimension as f64,
+            RealEvaluation::Schwefel { .. } => 418.9829 * dimension as f64,
+            _ => 0.0,
+        }
+    }
+
+    /// Usual search domain of a real evaluation, the same for every coordinate.
+    pub(crate) fn default_bounds(&self) -> (f64, f64) {
+        match self {
+            RealEvaluation::Sphere { .. } | RealEvaluation::Rastrigin { .. } => (-5.12, 5.12),
+            RealEvaluation::Rosenbrock { .. } => (-5.0, 10.0),
+            RealEvaluation::Schwefel { .. } => (-500.0, 500.0),
+            RealEvaluation::Ackley { .. } => (-32.768, 32.768),
+        }
+    }
+
+    pub(crate) fn length(&self) -> usize {
+        match self {
+            RealEvaluation::Sphere { dimension }
+            | RealEvaluation::Rastrigin { dimension }
+            | RealEvaluation::Rosenbrock { dimension }
+            | RealEvaluation::Schwefel { dimension }
+            | RealEvaluation::Ackley { dimension } => *dimension,
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use super::{Move, Problem, RealEvaluation, RealMoveType, State};
+
+/// Problem on a vector of real numbers within bounds, for continuous benchmark functions.
+///
+/// The start point is drawn uniformly within the bounds, so it changes with the seed.
+pub struct RealVectorProblem {
+    state: Vec<f64>,
+    best_solution: Vec<f64>,
+    bounds: Vec<(f64, f64)>,
+    warm_start: Option<Vec<f64>>,
+    move_type: RealMoveType,
+    evaluation: RealEvaluation,
+    rng: SmallRng,
+    replaced_value: f64,
+}
+impl RealVectorProblem {
+    /// Creates a new real vector problem.
+    ///
+    /// # Arguments
+    ///
+    /// * `bounds`: lower and upper bound of every coordinate, the usual domain of the
+    ///   evaluation if not given.
+    /// * `seed`: seed used to draw the start point.
+    pub fn new(
+        move_type: &RealMoveType,
+        evaluation: &RealEvaluation,
+        bounds: Option<Vec<(f64, f64)>>,
+        seed: Option<u64>,
+    ) -> Self {
+        let len = evaluation.length();
+        let bounds = bounds.unwrap_or(vec![evaluation.default_bounds(); len]);
+        assert_eq!(bounds.len(), len, "Bounds have the wrong length");
+        let mut mov = move_type.clone();
+        mov.set_size(len);
+        let rng = match seed {
+            Some(seed) => SmallRng::seed_from_u64(seed),
+            None => SmallRng::from_entropy(),
+        };
+        let mut real_vector_problem = RealVectorProblem {
+            state: vec![],
+            best_solution: vec![],
+            bounds,
+            warm_start: None,
+            move_type: mov,
+            evaluation: evaluation.clone(),
+            rng,
+            replaced_value: 0.0,
+        };
+        real_vector_problem.reset();
+        real_vector_problem
+    }
+
+    pub fn state(&self) -> &Vec<f64> {
+        &self.state
+    }
+
+    pub fn best_solution(&self) -> &Vec<f64> {
+        &self.best_solution
+    }
+
+    pub fn bounds(&self) -> &Vec<(f64, f64)> {
+        &self.bounds
+    }
+
+    pub fn set_eval_type(&mut self, eval_type: RealEvaluation) {
+        self.evaluation = eval_type;
+    }
+
+    pub fn set_move_type(&mut self, move_type: RealMoveType) {
+        self.move_type = move_type;
+        self.move_type.set_size(self.state.len());
+    }
+
+    /// Value of coordinate `index` after adding `step`, kept within the bounds.
+    fn perturbed(&self, index: usize, step: f64) -> f64 {
+        let (lower, upper) = self.bounds[index];
+        (self.state[index] + step).clamp(lower, upper)
+    }
+}
+impl Problem for RealVectorProblem {
+    fn get_mov(&mut self) -> Move {
+        self.move_type.get_mov()
+    }
+
+    fn get_all_mov(&mut self) -> Vec<Move> {
+        self.move_type.get_all_mov()
+    }
+
+    fn iter_mov(
+        &mut self,
+        _neighborhood: Option<usize>,
+        shuffle: bool,
+    ) -> Box<dyn Iterator<Item = Move>> {
+        Box::new(self.move_type.iter_mov(shuffle))
+    }
+
+    fn do_mov(&mut self, mov: &Move) {
+        match *mov {
+            Move::Perturb(index, step) => {
+                self.replaced_value = self.state[index];
+                self.state[index] = self.perturbed(index, step);
+            }
+            _ => mov.do_move(&mut self.state),
+        }
+    }
+
+    fn undo_mov(&mut self, mov: &Move) {
+        match *mov {
+            Move::Perturb(index, _) => self.state[index] = self.replaced_value,
+            _ => mov.undo_move(&mut self.state),
+        }
+    }
+
+    fn delta_eval(&mut self, mov: &Move) -> f64 {
+        match *mov {
+            Move::Perturb(index, step) => {
+                let value = self.perturbed(index, step);
+                self.evaluation.delta(&mut self.state, index, value)
+            }
+            _ => {
+                let first = self.evaluation.eval(&self.state);
+                mov.do_move(&mut self.state);
+                let sec = self.evaluation.eval(&self.state);
+                mov.undo_move(&mut self.state);
+                sec - first
+            }
+        }
+    }
+
+    fn eval(&self) -> f64 {
+        self.evaluation.eval(&self.state)
+    }
+
+    fn reset(&mut self) {
+        self.state = match &self.warm_start {
+            Some(x) => x.clone(),
+            None => self
+                .bounds
+                .iter()
+                .map(|(lower, upper)| self.rng.gen_range(*lower..=*upper))
+                .collect(),
+        };
+        self.best_solution = self.state.clone();
+    }
+
+    fn set_best(&mut self) {
+        self.best_solution = self.state.to_vec();
+    }
+
+    fn hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for x in &self.state {
+            x.to_bits().hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    fn neighborhood_count(&self) -> usize {
+        1
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.move_type.set_seed(seed);
+        self.rng = SmallRng::seed_from_u64(seed);
+    }
+
+    fn get_state(&self) -> Option<State> {
+        Some(State::Real(self.state.clone()))
+    }
+
+    fn get_best_solution(&self) -> Option<State> {
+        Some(State::Real(self.best_solution.clone()))
+    }
+
+    fn set_state(&mut self, state: &State) -> Result<(), String> {
+        self.state = state.real(&self.bounds)?.to_vec();
+        Ok(())
+    }
+
+    fn eval_state(&self, state: &State) -> Result<f64, String> {
+        Ok(self.evaluation.eval(state.real(&self.bounds)?))
+    }
+
+    fn set_warm_start(&mut self, warm_start: Option<State>) -> Result<(), String> {
+        self.warm_start = match warm_start {
+            Some(state) => Some(state.real(&self.bounds)?.to_vec()),
+            None => None,
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::local_search::{LocalSearch, SimulatedAnnealing};
+    use crate::problem::{Move, Problem, RealEvaluation, RealMoveType, RealVectorProblem};
+    use crate::simulated_annealing::{CoolingFunction, IterationsTemperature};
+    use crate::termination::TerminationFunction;
+
+    #[test]
+    fn delta_eval_test() {
+        let evaluations = vec![
+            RealEvaluation::sphere(4),
+            RealEvaluation::rastrigin(4),
+            RealEvaluation::rosenbrock(4),
+            RealEvaluation::schwefel(4),
+            RealEvaluation::ackley(4),
+        ];
+        let moves = vec![
+            Move::Perturb(0, 0.3),
+            Move::Perturb(3, -1.2),
+            Move::Perturb(1, 0.7),
+            Move::Swap(0, 2),
+            Move::Perturb(2, 100.0),
+            Move::Reverse(1, 3),
+        ];
+        for eval in evaluations {
+            let mut problem =
+                RealVectorProblem::new(&RealMoveType::gaussian(1.0, Some(0)), &eval, None, Some(0));
+            for mov in &moves {
+                let before = problem.eval();
+                let delta = problem.delta_eval(mov);
+                problem.do_mov(mov);
+                let after = problem.eval();
+                assert!((delta - (after - before)).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn bounds_test() {
+        let eval = RealEvaluation::sphere(2);
+        let bounds = vec![(-1.0, 1.0), (0.0, 2.0)];
+        let mut problem = RealVectorProblem::new(
+            &RealMoveType::uniform(0.5, Some(0)),
+            &eval,
+            Some(bounds),
+            Some(0),
+        );
+        let start = problem.state().clone();
+        problem.do_mov(&Move::Perturb(1, 10.0));
+        assert_eq!(problem.state()[1], 2.0);
+        problem.undo_mov(&Move::Perturb(1, 10.0));
+        assert_eq!(*problem.state(), start);
+
+        problem.set_seed(3);
+        problem.reset();
+        let first = problem.state().clone();
+        problem.set_seed(3);
+        problem.reset();
+        assert_eq!(*problem.state(), first);
+        assert!(first[0].abs() <= 1.0 && (0.0..=2.0).contains(&first[1]));
+    }
+
+    #[test]
+    fn simulated_annealing_test() {
+        let eval = RealEvaluation::sphere(3);
+        let problem: Arc<Mutex<dyn Problem>> = Arc::new(Mutex::new(RealVectorProblem::new(
+            &RealMoveType::gaussian(0.1, Some(0)),
+            &eval,
+            None,
+            Some(0),
+        )));
+        let start = problem.lock().unwrap().eval();
+        let cooling = CoolingFunction::geometric_cooling(0.5);
+        let termination = TerminationFunction::min_temp(1);
+        let iter = IterationsTemperature::const_iter_temp(1000);
+
+        let mut sim = SimulatedAnnealing::new(10, true, &problem, &termination, &cooling, &iter);
+        let data = sim.run(false, None).unwrap().last().unwrap().1;
+        assert!(data < start);
+        assert!(data < 0.5);
+    }
+}
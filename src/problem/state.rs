@@ -0,0 +1,111 @@
+use pyo3::prelude::*;
+
+/// A solution of a problem, tagged with the kind of problem it belongs to.
+#[derive(Clone, Debug, PartialEq)]
+pub enum State {
+    /// Order of the elements, used by [ArrayProblem](super::ArrayProblem).
+    Permutation(Vec<usize>),
+    /// Value of every position, used by [AssignmentProblem](super::AssignmentProblem).
+    Assignment(Vec<usize>),
+    /// Used by [BinaryProblem](super::BinaryProblem).
+    Binary(Vec<bool>),
+    /// Used by [RealVectorProblem](super::RealVectorProblem).
+    Real(Vec<f64>),
+}
+impl State {
+    /// The values if this is a permutation of `0..len`.
+    pub(crate) fn permutation(&self, len: usize) -> Result<&[usize], String> {
+        let State::Permutation(order) = self else {
+            return Err("Expected a permutation".to_string());
+        };
+        check_len(order.len(), len)?;
+        let mut seen = vec![false; len];
+        for &x in order {
+            if x >= len || seen[x] {
+                return Err(format!("State is not a permutation of 0..{}", len));
+            }
+            seen[x] = true;
+        }
+        Ok(order)
+    }
+
+    /// The values if this is an assignment of `len` values in `0..domain`.
+    pub(crate) fn assignment(&self, len: usize, domain: usize) -> Result<&[usize], String> {
+        let State::Assignment(values) = self else {
+            return Err("Expected an assignment".to_string());
+        };
+        check_len(values.len(), len)?;
+        if values.iter().any(|x| *x >= domain) {
+            return Err(format!("State contains a value outside 0..{}", domain));
+        }
+        Ok(values)
+    }
+
+    /// The bits if this is a binary state of length `len`.
+    pub(crate) fn binary(&self, len: usize) -> Result<&[bool], String> {
+        let State::Binary(bits) = self else {
+            return Err("Expected a binary state".to_string());
+        };
+        check_len(bits.len(), len)?;
+        Ok(bits)
+    }
+
+    /// The coordinates if this is a real vector within the bounds.
+    pub(crate) fn real(&self, bounds: &[(f64, f64)]) -> Result<&[f64], String> {
+        let State::Real(x) = self else {
+            return Err("Expected a real vector".to_string());
+        };
+        check_len(x.len(), bounds.len())?;
+        if x.iter()
+            .zip(bounds)
+            .any(|(x, (lower, upper))| x < lower || x > upper)
+        {
+            return Err("State is outside the bounds".to_string());
+        }
+        Ok(x)
+    }
+}
+
+/// States are given to Python as a list of their values.
+impl IntoPy<PyObject> for State {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        match self {
+            State::Permutation(values) | State::Assignment(values) => values.into_py(py),
+            State::Binary(bits) => bits.into_py(py),
+            State::Real(x) => x.into_py(py),
+        }
+    }
+}
+
+fn check_len(len: usize, expected: usize) -> Result<(), String> {
+    if len != expected {
+        return Err(format!("State has length {} instead of {}", len, expected));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::State;
+
+    #[test]
+    fn validation_test() {
+        let order = State::Permutation(vec![2, 0, 1]);
+        assert_eq!(order.permutation(3), Ok(&[2, 0, 1][..]));
+        assert!(order.permutation(4).is_err());
+        assert!(State::Permutation(vec![0, 0, 1]).permutation(3).is_err());
+        assert!(State::Permutation(vec![0, 3, 1]).permutation(3).is_err());
+        assert!(order.assignment(3, 3).is_err());
+
+        let values = State::Assignment(vec![0, 2, 2]);
+        assert!(values.assignment(3, 3).is_ok());
+        assert!(values.assignment(3, 2).is_err());
+
+        assert!(State::Binary(vec![true, false]).binary(2).is_ok());
+        assert!(State::Binary(vec![true]).binary(2).is_err());
+
+        let x = State::Real(vec![0.5, -2.0]);
+        assert!(x.real(&[(0.0, 1.0), (-2.0, 2.0)]).is_ok());
+        assert!(x.real(&[(0.0, 1.0), (-1.0, 2.0)]).is_err());
+    }
+}